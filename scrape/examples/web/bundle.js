@@ -0,0 +1,5 @@
+"use strict";(()=>{var a=(e,...t)=>e.join("");var Button=styled.button`
+  background: #e4002b;
+  color: ${e=>e.inverted?"#000":"#fff"};
+  &:hover { color: #fff; }
+`;document.getElementById("root").textContent="Ready: 100%; see docs";})();
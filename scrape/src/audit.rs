@@ -0,0 +1,216 @@
+use crate::color::{apca_min_font_size, Color, ColorVision, WcagCompliance};
+use crate::SiteColors;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContrastCheck {
+    pub foreground: Color,
+    pub background: Color,
+    /// Selector of the rule declaring the pair, `None` for pairs combined from the palette.
+    pub selector: Option<String>,
+    pub ratio: f64,
+    pub wcag: WcagCompliance,
+    /// APCA lightness contrast (Lc) of the foreground as text on the background.
+    pub apca: f64,
+    /// Smallest font size in px that normal weight text supports at the pair's Lc.
+    pub apca_min_font_size: Option<f64>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContrastAudit {
+    /// Pairs declared together by a CSS rule.
+    pub declared: Vec<ContrastCheck>,
+    /// Every combination of two distinct colors from the site's palette.
+    pub palette: Vec<ContrastCheck>,
+}
+
+/// Two palette colors that are distinct with typical color vision but not with `vision`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndistinguishablePair {
+    pub vision: ColorVision,
+    pub colors: (Color, Color),
+    /// CIEDE2000 difference with typical color vision.
+    pub difference: f64,
+    /// CIEDE2000 difference of the colors as simulated for `vision`.
+    pub simulated_difference: f64,
+}
+
+impl ContrastCheck {
+    /// Translucent foregrounds are measured as composited over the background.
+    pub fn new(foreground: Color, background: Color, selector: Option<String>) -> Self {
+        let composited = foreground.over(&background);
+        let ratio = composited.contrast_ratio(&background);
+        let apca = composited.apca_contrast(&background);
+        Self {
+            foreground,
+            background,
+            selector,
+            ratio,
+            wcag: WcagCompliance::from_ratio(ratio),
+            apca,
+            apca_min_font_size: apca_min_font_size(apca, 400),
+        }
+    }
+}
+
+impl ContrastAudit {
+    /// Declared pairs failing WCAG AA for normal text.
+    pub fn failing(&self) -> impl Iterator<Item = &ContrastCheck> {
+        self.declared
+            .iter()
+            .filter(|check| !check.wcag.aa_normal_text)
+    }
+}
+
+impl SiteColors {
+    /// Translucent backgrounds are composited over the page background.
+    pub fn contrast_audit(&self) -> ContrastAudit {
+        let declared = self
+            .pairs
+            .iter()
+            .map(|pair| {
+                ContrastCheck::new(
+                    pair.foreground,
+                    self.effective_color(&pair.background),
+                    Some(pair.selector.clone()),
+                )
+            })
+            .collect();
+        let colors: Vec<Color> = self
+            .color_counts()
+            .into_iter()
+            .map(|(color, _)| color)
+            .collect();
+        let mut palette = Vec::new();
+        for (i, foreground) in colors.iter().enumerate() {
+            for background in &colors[i + 1..] {
+                palette.push(ContrastCheck::new(*foreground, *background, None));
+            }
+        }
+        palette.sort_by(|a, b| b.ratio.total_cmp(&a.ratio));
+        ContrastAudit { declared, palette }
+    }
+
+    /// Palette pairs that fall within a CIEDE2000 `threshold` of each other for each
+    /// dichromacy while being further apart than it with typical color vision.
+    pub fn color_vision_audit(&self, threshold: f64) -> Vec<IndistinguishablePair> {
+        self.color_vision_audit_for(&ColorVision::DICHROMACIES, threshold)
+    }
+
+    pub fn color_vision_audit_for(
+        &self,
+        visions: &[ColorVision],
+        threshold: f64,
+    ) -> Vec<IndistinguishablePair> {
+        let colors: Vec<Color> = self
+            .color_counts()
+            .into_iter()
+            .map(|(color, _)| color)
+            .collect();
+        let mut pairs = Vec::new();
+        for vision in visions {
+            let simulated: Vec<Color> = colors.iter().map(|c| c.simulate(*vision)).collect();
+            for i in 0..colors.len() {
+                for j in i + 1..colors.len() {
+                    let difference = colors[i].delta_e_2000(&colors[j]);
+                    let simulated_difference = simulated[i].delta_e_2000(&simulated[j]);
+                    if difference > threshold && simulated_difference <= threshold {
+                        pairs.push(IndistinguishablePair {
+                            vision: *vision,
+                            colors: (colors[i], colors[j]),
+                            difference,
+                            simulated_difference,
+                        });
+                    }
+                }
+            }
+        }
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ColorPair;
+
+    #[test]
+    fn test_contrast_audit() {
+        let site = SiteColors {
+            colors: vec![
+                Color::rgb(0, 0, 0),
+                Color::rgb(255, 255, 255),
+                Color::rgb(0x77, 0x77, 0x77),
+            ],
+            pairs: vec![
+                ColorPair {
+                    selector: String::from("body"),
+                    foreground: Color::rgb(0, 0, 0),
+                    background: Color::rgb(255, 255, 255),
+                },
+                ColorPair {
+                    selector: String::from(".muted"),
+                    foreground: Color::rgb(0x77, 0x77, 0x77),
+                    background: Color::rgb(255, 255, 255),
+                },
+            ],
+            ..Default::default()
+        };
+        let audit = site.contrast_audit();
+        assert_eq!(2, audit.declared.len());
+        assert!(audit.declared[0].wcag.aaa_normal_text);
+        assert!(audit.declared[0].apca > 100.);
+        assert_eq!(Some(16.), audit.declared[0].apca_min_font_size);
+        assert_eq!(Some(String::from("body")), audit.declared[0].selector);
+        let failing: Vec<_> = audit.failing().collect();
+        assert_eq!(1, failing.len());
+        assert_eq!(Some(String::from(".muted")), failing[0].selector);
+        assert!(failing[0].wcag.aa_large_text);
+        assert_eq!(Some(24.), failing[0].apca_min_font_size);
+        assert_eq!(3, audit.palette.len());
+        assert_eq!(21., audit.palette[0].ratio);
+    }
+
+    #[test]
+    fn test_contrast_of_translucent_colors() {
+        let site = SiteColors {
+            pairs: vec![ColorPair {
+                selector: String::from(".overlay"),
+                foreground: Color::rgba(255, 255, 255, 51),
+                background: Color::rgba(0, 0, 0, 0),
+            }],
+            page_background: Some(Color::rgb(0, 0, 0)),
+            ..Default::default()
+        };
+        let check = &site.contrast_audit().declared[0];
+        assert_eq!(Color::rgba(255, 255, 255, 51), check.foreground);
+        assert_eq!(Color::rgb(0, 0, 0), check.background);
+        let composited = Color::rgb(51, 51, 51).contrast_ratio(&Color::rgb(0, 0, 0));
+        assert_eq!(composited, check.ratio);
+    }
+
+    #[test]
+    fn test_color_vision_audit() {
+        let site = SiteColors {
+            colors: vec![
+                Color::rgb(0xcc, 0x33, 0x33),
+                Color::rgb(0x66, 0x80, 0x33),
+                Color::rgb(0x00, 0x00, 0xff),
+            ],
+            ..Default::default()
+        };
+        let pairs = site.color_vision_audit(10.);
+        assert!(!pairs.is_empty());
+        for pair in &pairs {
+            assert_ne!(ColorVision::Tritanopia, pair.vision);
+            assert_eq!(
+                (Color::rgb(0xcc, 0x33, 0x33), Color::rgb(0x66, 0x80, 0x33)),
+                pair.colors
+            );
+            assert!(pair.simulated_difference <= 10.);
+            assert!(pair.difference > 10.);
+        }
+    }
+}
@@ -1,4 +1,24 @@
-#[derive(Debug, Eq, Hash, PartialEq)]
+pub use crate::color::blend::BlendMode;
+pub use crate::color::contrast::{apca_min_font_size, apca_min_font_weight, WcagCompliance};
+pub use crate::color::difference::*;
+pub use crate::color::format::ParseColorError;
+pub use crate::color::generate::{Harmony, RampStep, RAMP_STEPS};
+pub use crate::color::name::{ColorNames, ColorNamesError, NamedColor};
+pub use crate::color::space::{Cam16, Cam16Ucs, Lab, Oklab, Oklch, Xyz};
+pub use crate::color::vision::{ColorVision, CvdModel};
+
+mod blend;
+mod contrast;
+mod difference;
+mod format;
+mod generate;
+mod name;
+#[cfg(feature = "serde")]
+pub mod repr;
+mod space;
+mod vision;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
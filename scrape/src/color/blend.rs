@@ -0,0 +1,245 @@
+use crate::color::Color;
+
+/// Blend modes of CSS `mix-blend-mode` as defined by Compositing and Blending Level 1.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl Color {
+    /// Porter-Duff source-over compositing of `self` onto `backdrop`.
+    pub fn over(&self, backdrop: &Color) -> Color {
+        self.blend(backdrop, BlendMode::Normal)
+    }
+
+    /// Blends `self` as the source onto `backdrop` with `mode` and composites the result
+    /// with source-over.
+    pub fn blend(&self, backdrop: &Color, mode: BlendMode) -> Color {
+        let source = self.unit_rgb();
+        let back = backdrop.unit_rgb();
+        let alpha_s = self.a as f64 / 255.;
+        let alpha_b = backdrop.a as f64 / 255.;
+        let blended = blend_rgb(back, source, mode);
+        let alpha_o = alpha_s + alpha_b * (1. - alpha_s);
+        if alpha_o == 0. {
+            return Color::rgba(0, 0, 0, 0);
+        }
+        let [r, g, b] = [0, 1, 2].map(|i| {
+            let mixed = (1. - alpha_b) * source[i] + alpha_b * blended[i];
+            let co = alpha_s * mixed + alpha_b * back[i] * (1. - alpha_s);
+            unit_to_u8(co / alpha_o)
+        });
+        Color::rgba(r, g, b, unit_to_u8(alpha_o))
+    }
+
+    fn unit_rgb(&self) -> [f64; 3] {
+        [self.r, self.g, self.b].map(|c| c as f64 / 255.)
+    }
+}
+
+fn unit_to_u8(v: f64) -> u8 {
+    (v.clamp(0., 1.) * 255.).round() as u8
+}
+
+fn blend_rgb(cb: [f64; 3], cs: [f64; 3], mode: BlendMode) -> [f64; 3] {
+    let separable = |f: fn(f64, f64) -> f64| [0, 1, 2].map(|i| f(cb[i], cs[i]));
+    match mode {
+        BlendMode::Normal => cs,
+        BlendMode::Multiply => separable(multiply),
+        BlendMode::Screen => separable(screen),
+        BlendMode::Overlay => separable(|cb, cs| hard_light(cs, cb)),
+        BlendMode::Darken => separable(f64::min),
+        BlendMode::Lighten => separable(f64::max),
+        BlendMode::ColorDodge => separable(color_dodge),
+        BlendMode::ColorBurn => separable(color_burn),
+        BlendMode::HardLight => separable(hard_light),
+        BlendMode::SoftLight => separable(soft_light),
+        BlendMode::Difference => separable(|cb, cs| (cb - cs).abs()),
+        BlendMode::Exclusion => separable(|cb, cs| cb + cs - 2. * cb * cs),
+        BlendMode::Hue => set_lum(set_sat(cs, sat(cb)), lum(cb)),
+        BlendMode::Saturation => set_lum(set_sat(cb, sat(cs)), lum(cb)),
+        BlendMode::Color => set_lum(cs, lum(cb)),
+        BlendMode::Luminosity => set_lum(cb, lum(cs)),
+    }
+}
+
+fn multiply(cb: f64, cs: f64) -> f64 {
+    cb * cs
+}
+
+fn screen(cb: f64, cs: f64) -> f64 {
+    cb + cs - cb * cs
+}
+
+fn hard_light(cb: f64, cs: f64) -> f64 {
+    if cs <= 0.5 {
+        multiply(cb, 2. * cs)
+    } else {
+        screen(cb, 2. * cs - 1.)
+    }
+}
+
+fn color_dodge(cb: f64, cs: f64) -> f64 {
+    if cb == 0. {
+        0.
+    } else if cs == 1. {
+        1.
+    } else {
+        (cb / (1. - cs)).min(1.)
+    }
+}
+
+fn color_burn(cb: f64, cs: f64) -> f64 {
+    if cb == 1. {
+        1.
+    } else if cs == 0. {
+        0.
+    } else {
+        1. - ((1. - cb) / cs).min(1.)
+    }
+}
+
+fn soft_light(cb: f64, cs: f64) -> f64 {
+    if cs <= 0.5 {
+        cb - (1. - 2. * cs) * cb * (1. - cb)
+    } else {
+        let d = if cb <= 0.25 {
+            ((16. * cb - 12.) * cb + 4.) * cb
+        } else {
+            cb.sqrt()
+        };
+        cb + (2. * cs - 1.) * (d - cb)
+    }
+}
+
+fn lum(c: [f64; 3]) -> f64 {
+    0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2]
+}
+
+fn clip_color(c: [f64; 3]) -> [f64; 3] {
+    let l = lum(c);
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    c.map(|v| {
+        let mut v = v;
+        if n < 0. {
+            v = l + (v - l) * l / (l - n);
+        }
+        if x > 1. {
+            v = l + (v - l) * (1. - l) / (x - l);
+        }
+        v
+    })
+}
+
+fn set_lum(c: [f64; 3], l: f64) -> [f64; 3] {
+    let d = l - lum(c);
+    clip_color(c.map(|v| v + d))
+}
+
+fn sat(c: [f64; 3]) -> f64 {
+    c[0].max(c[1]).max(c[2]) - c[0].min(c[1]).min(c[2])
+}
+
+fn set_sat(c: [f64; 3], s: f64) -> [f64; 3] {
+    let mut order = [0, 1, 2];
+    order.sort_by(|a, b| c[*a].total_cmp(&c[*b]));
+    let [min, mid, max] = order;
+    let mut result = [0.; 3];
+    if c[max] > c[min] {
+        result[mid] = (c[mid] - c[min]) * s / (c[max] - c[min]);
+        result[max] = s;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_over() {
+        let white = Color::rgb(255, 255, 255);
+        assert_eq!(
+            Color::rgb(235, 235, 235),
+            Color::rgba(0, 0, 0, 20).over(&white)
+        );
+        assert_eq!(
+            Color::rgb(128, 0, 127),
+            Color::rgba(255, 0, 0, 128).over(&Color::rgb(0, 0, 255))
+        );
+        assert_eq!(white, Color::rgba(0, 0, 0, 0).over(&white));
+        assert_eq!(Color::rgb(1, 2, 3), Color::rgb(1, 2, 3).over(&white));
+        let translucent = Color::rgba(255, 0, 0, 128).over(&Color::rgba(0, 0, 255, 128));
+        assert_eq!(192, translucent.a);
+    }
+
+    #[test]
+    fn test_separable_blend_modes() {
+        let backdrop = Color::rgb(0x80, 0x40, 0xff);
+        let source = Color::rgb(0xff, 0x80, 0x00);
+        let cases = [
+            (BlendMode::Normal, Color::rgb(0xff, 0x80, 0x00)),
+            (BlendMode::Multiply, Color::rgb(0x80, 0x20, 0x00)),
+            (BlendMode::Screen, Color::rgb(0xff, 0xa0, 0xff)),
+            (BlendMode::Overlay, Color::rgb(0xff, 0x40, 0xff)),
+            (BlendMode::Darken, Color::rgb(0x80, 0x40, 0x00)),
+            (BlendMode::Lighten, Color::rgb(0xff, 0x80, 0xff)),
+            (BlendMode::ColorDodge, Color::rgb(0xff, 0x81, 0xff)),
+            (BlendMode::ColorBurn, Color::rgb(0x80, 0x00, 0xff)),
+            (BlendMode::HardLight, Color::rgb(0xff, 0x41, 0x00)),
+            (BlendMode::Difference, Color::rgb(0x7f, 0x40, 0xff)),
+            (BlendMode::Exclusion, Color::rgb(0x7f, 0x80, 0xff)),
+        ];
+        for (mode, expected) in cases {
+            assert_eq!(expected, source.blend(&backdrop, mode), "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn test_non_separable_blend_modes() {
+        let gray = Color::rgb(0x80, 0x80, 0x80);
+        let red = Color::rgb(0xff, 0x00, 0x00);
+        let luminosity = red.blend(&gray, BlendMode::Luminosity);
+        assert_eq!(luminosity.r, luminosity.g);
+        assert_eq!(luminosity.g, luminosity.b);
+        assert_eq!(gray, red.blend(&gray, BlendMode::Saturation));
+        let colored = red.blend(&gray, BlendMode::Color);
+        assert!(colored.r > colored.g && colored.g == colored.b);
+        assert_eq!(
+            gray,
+            gray.blend(&red, BlendMode::Hue)
+                .blend(&gray, BlendMode::Hue)
+        );
+    }
+
+    #[test]
+    fn test_blend_with_translucent_source() {
+        let backdrop = Color::rgb(0x80, 0x80, 0x80);
+        let source = Color::rgba(0x00, 0x00, 0x00, 0x80);
+        assert_eq!(
+            Color::rgb(0x40, 0x40, 0x40),
+            source.blend(&backdrop, BlendMode::Normal)
+        );
+        assert_eq!(
+            Color::rgb(0x60, 0x60, 0x60),
+            Color::rgba(0x80, 0x80, 0x80, 0x80).blend(&backdrop, BlendMode::Multiply)
+        );
+    }
+}
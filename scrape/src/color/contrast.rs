@@ -0,0 +1,266 @@
+use crate::color::Color;
+
+/// Which WCAG 2.1 contrast success criteria a color pair satisfies.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WcagCompliance {
+    pub aa_normal_text: bool,
+    pub aa_large_text: bool,
+    pub aaa_normal_text: bool,
+    pub aaa_large_text: bool,
+}
+
+impl WcagCompliance {
+    pub fn from_ratio(ratio: f64) -> Self {
+        Self {
+            aa_normal_text: ratio >= 4.5,
+            aa_large_text: ratio >= 3.,
+            aaa_normal_text: ratio >= 7.,
+            aaa_large_text: ratio >= 4.5,
+        }
+    }
+}
+
+// APCA 0.0.98G-4g constants
+const APCA_TRC: f64 = 2.4;
+const APCA_NORM_BG: f64 = 0.56;
+const APCA_NORM_TXT: f64 = 0.57;
+const APCA_REV_TXT: f64 = 0.62;
+const APCA_REV_BG: f64 = 0.65;
+const APCA_BLACK_THRESHOLD: f64 = 0.022;
+const APCA_BLACK_CLAMP: f64 = 1.414;
+const APCA_SCALE: f64 = 1.14;
+const APCA_LOW_OFFSET: f64 = 0.027;
+const APCA_LOW_CLIP: f64 = 0.1;
+const APCA_DELTA_Y_MIN: f64 = 0.0005;
+
+const APCA_FONT_WEIGHTS: [u16; 9] = [100, 200, 300, 400, 500, 600, 700, 800, 900];
+
+// Minimum font sizes in px by weight for the APCA readability criterion's bronze simple
+// mode, rows are the minimum absolute Lc of the level
+const APCA_FONT_SIZES: [(f64, [Option<f64>; 9]); 4] = [
+    (
+        90.,
+        [
+            None,
+            None,
+            Some(18.),
+            Some(16.),
+            Some(14.),
+            Some(14.),
+            Some(14.),
+            Some(14.),
+            Some(14.),
+        ],
+    ),
+    (
+        75.,
+        [
+            None,
+            None,
+            Some(24.),
+            Some(18.),
+            Some(16.),
+            Some(16.),
+            Some(14.),
+            Some(14.),
+            Some(14.),
+        ],
+    ),
+    (
+        60.,
+        [
+            None,
+            Some(48.),
+            Some(36.),
+            Some(24.),
+            Some(21.),
+            Some(18.),
+            Some(16.),
+            Some(16.),
+            Some(16.),
+        ],
+    ),
+    (
+        45.,
+        [
+            None,
+            None,
+            None,
+            Some(36.),
+            Some(36.),
+            Some(36.),
+            Some(24.),
+            Some(24.),
+            Some(24.),
+        ],
+    ),
+];
+
+/// Smallest font size in px that text of `weight` may use at an APCA contrast of `lc`,
+/// `None` when the contrast is too low for readable text of that weight.
+pub fn apca_min_font_size(lc: f64, weight: u16) -> Option<f64> {
+    let column = APCA_FONT_WEIGHTS
+        .iter()
+        .rposition(|w| *w <= weight)
+        .unwrap_or(0);
+    APCA_FONT_SIZES
+        .iter()
+        .filter(|(min_lc, _)| lc.abs() >= *min_lc)
+        .filter_map(|(_, sizes)| sizes[column])
+        .reduce(f64::min)
+}
+
+/// Lightest font weight that text of `font_size` px may use at an APCA contrast of `lc`.
+pub fn apca_min_font_weight(lc: f64, font_size: f64) -> Option<u16> {
+    APCA_FONT_WEIGHTS.into_iter().find(|weight| {
+        apca_min_font_size(lc, *weight).is_some_and(|min_size| font_size >= min_size)
+    })
+}
+
+impl Color {
+    /// Lightness contrast of `self` as text on `background` per APCA, the WCAG 3 draft
+    /// method. Positive for dark text on a light background and negative for light text
+    /// on a dark background. Alpha is ignored.
+    pub fn apca_contrast(&self, background: &Color) -> f64 {
+        let text_y = self.apca_luminance();
+        let background_y = background.apca_luminance();
+        if (background_y - text_y).abs() < APCA_DELTA_Y_MIN {
+            return 0.;
+        }
+        let lc = if background_y > text_y {
+            let sapc = (background_y.powf(APCA_NORM_BG) - text_y.powf(APCA_NORM_TXT)) * APCA_SCALE;
+            if sapc < APCA_LOW_CLIP {
+                0.
+            } else {
+                sapc - APCA_LOW_OFFSET
+            }
+        } else {
+            let sapc = (background_y.powf(APCA_REV_BG) - text_y.powf(APCA_REV_TXT)) * APCA_SCALE;
+            if sapc > -APCA_LOW_CLIP {
+                0.
+            } else {
+                sapc + APCA_LOW_OFFSET
+            }
+        };
+        lc * 100.
+    }
+
+    fn apca_luminance(&self) -> f64 {
+        let linear = |channel: u8| (channel as f64 / 255.).powf(APCA_TRC);
+        let y =
+            0.2126729 * linear(self.r) + 0.7151522 * linear(self.g) + 0.0721750 * linear(self.b);
+        if y > APCA_BLACK_THRESHOLD {
+            y
+        } else {
+            y + (APCA_BLACK_THRESHOLD - y).powf(APCA_BLACK_CLAMP)
+        }
+    }
+
+    /// Relative luminance as defined by WCAG 2.1, alpha is ignored.
+    pub fn relative_luminance(&self) -> f64 {
+        let linear = |channel: u8| {
+            let c = channel as f64 / 255.;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// WCAG 2.1 contrast ratio between 1 and 21, the order of the colors does not matter.
+    pub fn contrast_ratio(&self, other: &Color) -> f64 {
+        let l1 = self.relative_luminance();
+        let l2 = other.relative_luminance();
+        (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+    }
+
+    pub fn wcag_compliance(&self, other: &Color) -> WcagCompliance {
+        WcagCompliance::from_ratio(self.contrast_ratio(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black = Color::rgb(0, 0, 0);
+        let white = Color::rgb(255, 255, 255);
+        assert_eq!(21., black.contrast_ratio(&white));
+        assert_eq!(21., white.contrast_ratio(&black));
+        assert_eq!(1., white.contrast_ratio(&white));
+        let gray = Color::rgb(0x76, 0x76, 0x76);
+        assert!((gray.contrast_ratio(&white) - 4.54).abs() < 0.01);
+        let gray = Color::rgb(0x77, 0x77, 0x77);
+        assert!((gray.contrast_ratio(&white) - 4.48).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_apca_contrast() {
+        let cases = [
+            ("888888", "ffffff", 63.056469930209424),
+            ("ffffff", "888888", -68.54146436644962),
+            ("000000", "aaaaaa", 58.146262578561334),
+            ("aaaaaa", "000000", -56.24113336839742),
+        ];
+        let parse = |hex: &str| {
+            let v = u32::from_str_radix(hex, 16).unwrap();
+            Color::rgb((v >> 16) as u8, (v >> 8) as u8, v as u8)
+        };
+        for (text, background, expected) in cases {
+            let lc = parse(text).apca_contrast(&parse(background));
+            assert!(
+                (lc - expected).abs() < 1e-9,
+                "{text} on {background} was {lc}"
+            );
+        }
+        let gray = Color::rgb(0x77, 0x77, 0x77);
+        assert_eq!(0., gray.apca_contrast(&gray));
+    }
+
+    #[test]
+    fn test_apca_font_lookup() {
+        assert_eq!(Some(16.), apca_min_font_size(90., 400));
+        assert_eq!(Some(16.), apca_min_font_size(-95., 450));
+        assert_eq!(Some(18.), apca_min_font_size(75., 400));
+        assert_eq!(Some(24.), apca_min_font_size(60., 400));
+        assert_eq!(Some(36.), apca_min_font_size(45., 400));
+        assert_eq!(None, apca_min_font_size(45., 300));
+        assert_eq!(None, apca_min_font_size(30., 900));
+        assert_eq!(Some(400), apca_min_font_weight(75., 18.));
+        assert_eq!(Some(700), apca_min_font_weight(60., 16.));
+        assert_eq!(None, apca_min_font_weight(60., 12.));
+    }
+
+    #[test]
+    fn test_wcag_compliance() {
+        let white = Color::rgb(255, 255, 255);
+        assert_eq!(
+            WcagCompliance {
+                aa_normal_text: true,
+                aa_large_text: true,
+                aaa_normal_text: false,
+                aaa_large_text: true,
+            },
+            Color::rgb(0x76, 0x76, 0x76).wcag_compliance(&white)
+        );
+        assert_eq!(
+            WcagCompliance {
+                aa_normal_text: false,
+                aa_large_text: true,
+                aaa_normal_text: false,
+                aaa_large_text: false,
+            },
+            Color::rgb(0x77, 0x77, 0x77).wcag_compliance(&white)
+        );
+        assert!(
+            Color::rgb(0x59, 0x59, 0x59)
+                .wcag_compliance(&white)
+                .aaa_normal_text
+        );
+    }
+}
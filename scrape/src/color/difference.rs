@@ -0,0 +1,293 @@
+use crate::color::space::{Cam16Ucs, Lab, Oklab};
+use crate::color::Color;
+
+/// Color difference formulas. Every metric other than `Rgb` ignores alpha.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DifferenceMetric {
+    /// Euclidean distance between 8-bit sRGB channels.
+    Rgb,
+    Cie76,
+    /// CIE94 with graphic arts weighting.
+    Cie94,
+    Ciede2000,
+    Oklab,
+    Cam16Ucs,
+}
+
+impl Color {
+    pub fn difference(&self, other: &Color, metric: DifferenceMetric) -> f64 {
+        match metric {
+            DifferenceMetric::Rgb => self.distance_rgb(other),
+            DifferenceMetric::Cie76 => self.delta_e_76(other),
+            DifferenceMetric::Cie94 => self.delta_e_94(other),
+            DifferenceMetric::Ciede2000 => self.delta_e_2000(other),
+            DifferenceMetric::Oklab => self.distance_oklab(other),
+            DifferenceMetric::Cam16Ucs => self.distance_cam16_ucs(other),
+        }
+    }
+
+    pub fn distance_rgb(&self, other: &Color) -> f64 {
+        let d = |a: u8, b: u8| (a as f64 - b as f64).powi(2);
+        (d(self.r, other.r) + d(self.g, other.g) + d(self.b, other.b)).sqrt()
+    }
+
+    pub fn delta_e_76(&self, other: &Color) -> f64 {
+        delta_e_76(&self.to_lab(), &other.to_lab())
+    }
+
+    /// CIE94 is not symmetric, `self` is used as the reference color.
+    pub fn delta_e_94(&self, other: &Color) -> f64 {
+        delta_e_94(&self.to_lab(), &other.to_lab())
+    }
+
+    pub fn delta_e_2000(&self, other: &Color) -> f64 {
+        delta_e_2000(&self.to_lab(), &other.to_lab())
+    }
+
+    pub fn distance_oklab(&self, other: &Color) -> f64 {
+        distance_oklab(&self.to_oklab(), &other.to_oklab())
+    }
+
+    pub fn distance_cam16_ucs(&self, other: &Color) -> f64 {
+        distance_cam16_ucs(&self.to_cam16_ucs(), &other.to_cam16_ucs())
+    }
+}
+
+pub fn delta_e_76(lab1: &Lab, lab2: &Lab) -> f64 {
+    ((lab1.l - lab2.l).powi(2) + (lab1.a - lab2.a).powi(2) + (lab1.b - lab2.b).powi(2)).sqrt()
+}
+
+pub fn delta_e_94(reference: &Lab, sample: &Lab) -> f64 {
+    let (k1, k2) = (0.045, 0.015);
+    let c1 = reference.chroma();
+    let c2 = sample.chroma();
+    let dl = reference.l - sample.l;
+    let dc = c1 - c2;
+    let dh_sq =
+        ((reference.a - sample.a).powi(2) + (reference.b - sample.b).powi(2) - dc.powi(2)).max(0.);
+    let sc = 1. + k1 * c1;
+    let sh = 1. + k2 * c1;
+    (dl.powi(2) + (dc / sc).powi(2) + dh_sq / sh.powi(2)).sqrt()
+}
+
+pub fn delta_e_2000(lab1: &Lab, lab2: &Lab) -> f64 {
+    let c_bar = (lab1.chroma() + lab2.chroma()) / 2.;
+    let c_bar7 = c_bar.powi(7);
+    let g = 0.5 * (1. - (c_bar7 / (c_bar7 + 25_f64.powi(7))).sqrt());
+    let a1 = (1. + g) * lab1.a;
+    let a2 = (1. + g) * lab2.a;
+    let c1 = a1.hypot(lab1.b);
+    let c2 = a2.hypot(lab2.b);
+    let hue = |b: f64, a: f64| {
+        if a == 0. && b == 0. {
+            0.
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.)
+        }
+    };
+    let h1 = hue(lab1.b, a1);
+    let h2 = hue(lab2.b, a2);
+
+    let dl = lab2.l - lab1.l;
+    let dc = c2 - c1;
+    let dh = if c1 * c2 == 0. {
+        0.
+    } else if (h2 - h1).abs() <= 180. {
+        h2 - h1
+    } else if h2 - h1 > 180. {
+        h2 - h1 - 360.
+    } else {
+        h2 - h1 + 360.
+    };
+    let dh_big = 2. * (c1 * c2).sqrt() * (dh / 2.).to_radians().sin();
+
+    let l_bar = (lab1.l + lab2.l) / 2.;
+    let c_bar = (c1 + c2) / 2.;
+    let h_bar = if c1 * c2 == 0. {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180. {
+        (h1 + h2) / 2.
+    } else if h1 + h2 < 360. {
+        (h1 + h2 + 360.) / 2.
+    } else {
+        (h1 + h2 - 360.) / 2.
+    };
+    let t = 1. - 0.17 * (h_bar - 30.).to_radians().cos()
+        + 0.24 * (2. * h_bar).to_radians().cos()
+        + 0.32 * (3. * h_bar + 6.).to_radians().cos()
+        - 0.20 * (4. * h_bar - 63.).to_radians().cos();
+    let d_theta = 30. * (-((h_bar - 275.) / 25.).powi(2)).exp();
+    let c_bar7 = c_bar.powi(7);
+    let rc = 2. * (c_bar7 / (c_bar7 + 25_f64.powi(7))).sqrt();
+    let sl = 1. + 0.015 * (l_bar - 50.).powi(2) / (20. + (l_bar - 50.).powi(2)).sqrt();
+    let sc = 1. + 0.045 * c_bar;
+    let sh = 1. + 0.015 * c_bar * t;
+    let rt = -(2. * d_theta).to_radians().sin() * rc;
+
+    ((dl / sl).powi(2) + (dc / sc).powi(2) + (dh_big / sh).powi(2) + rt * (dc / sc) * (dh_big / sh))
+        .sqrt()
+}
+
+pub fn distance_oklab(lab1: &Oklab, lab2: &Oklab) -> f64 {
+    ((lab1.l - lab2.l).powi(2) + (lab1.a - lab2.a).powi(2) + (lab1.b - lab2.b).powi(2)).sqrt()
+}
+
+pub fn distance_cam16_ucs(ucs1: &Cam16Ucs, ucs2: &Cam16Ucs) -> f64 {
+    ((ucs1.j - ucs2.j).powi(2) + (ucs1.a - ucs2.a).powi(2) + (ucs1.b - ucs2.b).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::space::ViewingConditions;
+
+    fn lab(l: f64, a: f64, b: f64) -> Lab {
+        Lab { l, a, b }
+    }
+
+    fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+        assert!(
+            (expected - actual).abs() <= tolerance,
+            "expected {expected} but was {actual}"
+        );
+    }
+
+    #[test]
+    fn test_delta_e_2000_sharma_data_set() {
+        // Sharma, Wu and Dalal (2005) test data for the CIEDE2000 formula
+        let data: [[f64; 7]; 34] = [
+            [50.0000, 2.6772, -79.7751, 50.0000, 0.0000, -82.7485, 2.0425],
+            [50.0000, 3.1571, -77.2803, 50.0000, 0.0000, -82.7485, 2.8615],
+            [50.0000, 2.8361, -74.0200, 50.0000, 0.0000, -82.7485, 3.4412],
+            [
+                50.0000, -1.3802, -84.2814, 50.0000, 0.0000, -82.7485, 1.0000,
+            ],
+            [
+                50.0000, -1.1848, -84.8006, 50.0000, 0.0000, -82.7485, 1.0000,
+            ],
+            [
+                50.0000, -0.9009, -85.5211, 50.0000, 0.0000, -82.7485, 1.0000,
+            ],
+            [50.0000, 0.0000, 0.0000, 50.0000, -1.0000, 2.0000, 2.3669],
+            [50.0000, -1.0000, 2.0000, 50.0000, 0.0000, 0.0000, 2.3669],
+            [50.0000, 2.4900, -0.0010, 50.0000, -2.4900, 0.0009, 7.1792],
+            [50.0000, 2.4900, -0.0010, 50.0000, -2.4900, 0.0010, 7.1792],
+            [50.0000, 2.4900, -0.0010, 50.0000, -2.4900, 0.0011, 7.2195],
+            [50.0000, 2.4900, -0.0010, 50.0000, -2.4900, 0.0012, 7.2195],
+            [50.0000, -0.0010, 2.4900, 50.0000, 0.0009, -2.4900, 4.8045],
+            [50.0000, -0.0010, 2.4900, 50.0000, 0.0010, -2.4900, 4.8045],
+            [50.0000, -0.0010, 2.4900, 50.0000, 0.0011, -2.4900, 4.7461],
+            [50.0000, 2.5000, 0.0000, 50.0000, 0.0000, -2.5000, 4.3065],
+            [50.0000, 2.5000, 0.0000, 73.0000, 25.0000, -18.0000, 27.1492],
+            [50.0000, 2.5000, 0.0000, 61.0000, -5.0000, 29.0000, 22.8977],
+            [50.0000, 2.5000, 0.0000, 56.0000, -27.0000, -3.0000, 31.9030],
+            [50.0000, 2.5000, 0.0000, 58.0000, 24.0000, 15.0000, 19.4535],
+            [50.0000, 2.5000, 0.0000, 50.0000, 3.1736, 0.5854, 1.0000],
+            [50.0000, 2.5000, 0.0000, 50.0000, 3.2972, 0.0000, 1.0000],
+            [50.0000, 2.5000, 0.0000, 50.0000, 1.8634, 0.5757, 1.0000],
+            [50.0000, 2.5000, 0.0000, 50.0000, 3.2592, 0.3350, 1.0000],
+            [
+                60.2574, -34.0099, 36.2677, 60.4626, -34.1751, 39.4387, 1.2644,
+            ],
+            [
+                63.0109, -31.0961, -5.8663, 62.8187, -29.7946, -4.0864, 1.2630,
+            ],
+            [61.2901, 3.7196, -5.3901, 61.4292, 2.2480, -4.9620, 1.8731],
+            [35.0831, -44.1164, 3.7933, 35.0232, -40.0716, 1.5901, 1.8645],
+            [
+                22.7233, 20.0904, -46.6940, 23.0331, 14.9730, -42.5619, 2.0373,
+            ],
+            [36.4612, 47.8580, 18.3852, 36.2715, 50.5065, 21.2231, 1.4146],
+            [90.8027, -2.0831, 1.4410, 91.1528, -1.6435, 0.0447, 1.4441],
+            [90.9257, -0.5406, -0.9208, 88.6381, -0.8985, -0.7239, 1.5381],
+            [6.7747, -0.2908, -2.4247, 5.8714, -0.0985, -2.2286, 0.6377],
+            [2.0776, 0.0795, -1.1350, 0.9033, -0.0636, -0.5514, 0.9082],
+        ];
+        for [l1, a1, b1, l2, a2, b2, expected] in data {
+            let lab1 = lab(l1, a1, b1);
+            let lab2 = lab(l2, a2, b2);
+            assert_close(expected, delta_e_2000(&lab1, &lab2), 1e-4);
+            assert_close(expected, delta_e_2000(&lab2, &lab1), 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_delta_e_76_and_94() {
+        let lab1 = lab(100., 21.57210357, 272.22819350);
+        let lab2 = lab(100., 426.67945353, 72.39590835);
+        assert_close(451.713301974, delta_e_76(&lab1, &lab2), 1e-6);
+        assert_close(83.779225500, delta_e_94(&lab1, &lab2), 1e-6);
+        let lab1 = lab(50., 2.6772, -79.7751);
+        let lab2 = lab(50., 0., -82.7485);
+        assert_close(4.0011, delta_e_76(&lab1, &lab2), 1e-4);
+        assert_close(1.3950, delta_e_94(&lab1, &lab2), 1e-4);
+    }
+
+    #[test]
+    fn test_distance_rgb() {
+        assert_eq!(0., Color::rgb(1, 2, 3).distance_rgb(&Color::rgb(1, 2, 3)));
+        assert_eq!(5., Color::rgb(0, 3, 0).distance_rgb(&Color::rgb(4, 0, 0)));
+        let black = Color::rgb(0, 0, 0);
+        let white = Color::rgb(255, 255, 255);
+        assert_close(441.672956, black.distance_rgb(&white), 1e-6);
+    }
+
+    #[test]
+    fn test_distance_oklab() {
+        let black = Color::rgb(0, 0, 0);
+        let white = Color::rgb(255, 255, 255);
+        assert_close(1., black.distance_oklab(&white), 1e-6);
+        let red = Color::rgb(255, 0, 0);
+        let blue = Color::rgb(0, 0, 255);
+        // oklab(0.627955 0.224863 0.125846) to oklab(0.452014 -0.032457 -0.311528)
+        assert_close(0.537090, red.distance_oklab(&blue), 1e-5);
+    }
+
+    #[test]
+    fn test_distance_cam16_ucs() {
+        let black = Color::rgb(0, 0, 0);
+        let white = Color::rgb(255, 255, 255);
+        let gray = Color::rgb(119, 119, 119);
+        assert_eq!(0., gray.distance_cam16_ucs(&gray));
+        assert_close(100., white.to_cam16().j, 1e-9);
+        assert_close(
+            white.distance_cam16_ucs(&black),
+            black.distance_cam16_ucs(&white),
+            1e-12,
+        );
+        assert!(gray.distance_cam16_ucs(&white) < black.distance_cam16_ucs(&white));
+        // J' = 1.7J / (1 + 0.007J) maps J = 100 to J' = 100
+        assert_close(100., white.to_cam16_ucs().j, 1e-9);
+    }
+
+    #[test]
+    fn test_distance_cam16_ucs_reference() {
+        // the worked example of Li et al. (2017) and CIE 248:2022, J = 41.731208,
+        // M = 0.107437, h = 217.067960, against black, which maps to the UCS origin
+        let vc = ViewingConditions::new([95.05, 100., 108.88], 318.31, 20., (1.0, 0.69, 1.0));
+        let sample = vc.cam16([19.01, 20.00, 21.78]).to_ucs();
+        let black = vc.cam16([0., 0., 0.]).to_ucs();
+        assert_close(54.904450, sample.j, 1e-4);
+        assert_close(-0.085621, sample.a, 1e-4);
+        assert_close(-0.064680, sample.b, 1e-4);
+        assert_close(54.904555, distance_cam16_ucs(&sample, &black), 1e-4);
+    }
+
+    #[test]
+    fn test_similar_colors_are_closer_for_every_metric() {
+        let base = Color::rgb(51, 51, 51);
+        let near = Color::rgb(52, 52, 52);
+        let far = Color::rgb(51, 51, 200);
+        for metric in [
+            DifferenceMetric::Rgb,
+            DifferenceMetric::Cie76,
+            DifferenceMetric::Cie94,
+            DifferenceMetric::Ciede2000,
+            DifferenceMetric::Oklab,
+            DifferenceMetric::Cam16Ucs,
+        ] {
+            assert!(base.difference(&near, metric) < base.difference(&far, metric));
+            assert_eq!(0., base.difference(&base, metric));
+        }
+    }
+}
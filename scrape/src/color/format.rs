@@ -0,0 +1,329 @@
+use std::fmt;
+use std::str::FromStr;
+
+use css_color::Srgb;
+use thiserror::*;
+
+use crate::color::{Color, Oklab, Oklch};
+
+#[derive(Error, Debug, PartialEq)]
+#[error("invalid CSS color: {0}")]
+pub struct ParseColorError(pub String);
+
+/// Parses hex notation, color keywords and the `rgb()`, `hsl()`, `hwb()`, `oklab()` and
+/// `oklch()` functions, rounding channels to the nearest 8-bit value.
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let css = s.trim().to_ascii_lowercase();
+        let parsed = if let Some(args) = function_args(&css, "oklch") {
+            parse_oklab_fn(args, true)
+        } else if let Some(args) = function_args(&css, "oklab") {
+            parse_oklab_fn(args, false)
+        } else {
+            css.parse::<Srgb>().ok().map(Color::from)
+        };
+        parsed.ok_or_else(|| ParseColorError(s.to_string()))
+    }
+}
+
+/// Formats as `#rrggbb`, or `#rrggbbaa` when translucent.
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+/// Formats as `rrggbb` or `rrggbbaa`, the alternate flag prefixes `#`.
+impl fmt::LowerHex for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = self.to_hex();
+        if f.alternate() {
+            f.write_str(&hex)
+        } else {
+            f.write_str(&hex[1..])
+        }
+    }
+}
+
+impl fmt::UpperHex for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = self.to_hex().to_ascii_uppercase();
+        if f.alternate() {
+            f.write_str(&hex)
+        } else {
+            f.write_str(&hex[1..])
+        }
+    }
+}
+
+impl Color {
+    pub fn is_opaque(&self) -> bool {
+        self.a == 255
+    }
+
+    /// `#rrggbb`, or `#rrggbbaa` when translucent.
+    pub fn to_hex(&self) -> String {
+        if self.is_opaque() {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+
+    /// Shortest hex notation, using `#rgb` or `#rgba` when every channel repeats a digit.
+    pub fn to_hex_short(&self) -> String {
+        let channels = [self.r, self.g, self.b, self.a];
+        if channels.iter().all(|c| c % 17 == 0) {
+            let short: String = channels
+                .iter()
+                .take(if self.is_opaque() { 3 } else { 4 })
+                .map(|c| format!("{:x}", c / 17))
+                .collect();
+            format!("#{short}")
+        } else {
+            self.to_hex()
+        }
+    }
+
+    /// Modern space separated syntax such as `rgb(255 0 0)` or `rgb(255 0 0 / 0.5)`.
+    pub fn to_rgb_css(&self) -> String {
+        format!(
+            "rgb({} {} {}{})",
+            self.r,
+            self.g,
+            self.b,
+            self.alpha_suffix()
+        )
+    }
+
+    /// Legacy comma separated syntax such as `rgb(255, 0, 0)` or `rgba(255, 0, 0, 0.5)`.
+    pub fn to_rgb_legacy_css(&self) -> String {
+        if self.is_opaque() {
+            format!("rgb({}, {}, {})", self.r, self.g, self.b)
+        } else {
+            format!(
+                "rgba({}, {}, {}, {})",
+                self.r,
+                self.g,
+                self.b,
+                decimal(self.a as f64 / 255., 3)
+            )
+        }
+    }
+
+    pub fn to_hsl_css(&self) -> String {
+        let (h, s, l) = self.hsl();
+        format!(
+            "hsl({} {}% {}%{})",
+            decimal(h, 2),
+            decimal(s * 100., 2),
+            decimal(l * 100., 2),
+            self.alpha_suffix()
+        )
+    }
+
+    pub fn to_oklch_css(&self) -> String {
+        let Oklch { l, c, h } = self.to_oklch();
+        let (c, h) = if c < 0.000005 { (0., 0.) } else { (c, h) };
+        format!(
+            "oklch({}% {} {}{})",
+            decimal(l * 100., 3),
+            decimal(c, 5),
+            decimal(h, 3),
+            self.alpha_suffix()
+        )
+    }
+
+    /// Hue in degrees with saturation and lightness between 0 and 1.
+    pub(crate) fn hsl(&self) -> (f64, f64, f64) {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| c as f64 / 255.);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.;
+        let d = max - min;
+        if d == 0. {
+            return (0., 0., l);
+        }
+        let s = d / (1. - (2. * l - 1.).abs());
+        let h = if max == r {
+            60. * ((g - b) / d).rem_euclid(6.)
+        } else if max == g {
+            60. * ((b - r) / d + 2.)
+        } else {
+            60. * ((r - g) / d + 4.)
+        };
+        (h, s, l)
+    }
+
+    fn alpha_suffix(&self) -> String {
+        if self.is_opaque() {
+            String::new()
+        } else {
+            format!(" / {}", decimal(self.a as f64 / 255., 3))
+        }
+    }
+}
+
+/// Formats with at most `places` decimal places and no trailing zeros.
+fn decimal(v: f64, places: usize) -> String {
+    let formatted = format!("{v:.places$}");
+    let trimmed = if formatted.contains('.') {
+        formatted.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        formatted.as_str()
+    };
+    match trimmed {
+        "-0" => String::from("0"),
+        _ => trimmed.to_string(),
+    }
+}
+
+fn unit_to_u8(v: f64) -> u8 {
+    (v.clamp(0., 1.) * 255.).round() as u8
+}
+
+fn function_args<'a>(css: &'a str, name: &str) -> Option<&'a str> {
+    css.strip_prefix(name)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')
+}
+
+/// Parses the arguments of `oklch()`, or `oklab()` when `polar` is false.
+fn parse_oklab_fn(args: &str, polar: bool) -> Option<Color> {
+    let (components, alpha) = match args.split_once('/') {
+        Some((components, alpha)) => (components, Some(alpha.trim())),
+        None => (args, None),
+    };
+    let components: Vec<&str> = components.split_whitespace().collect();
+    let [l, second, third] = components[..] else {
+        return None;
+    };
+    let l = parse_number_or_percentage(l, 1.)?;
+    let second = parse_number_or_percentage(second, 0.4)?;
+    let oklab = if polar {
+        let h = third
+            .strip_suffix("deg")
+            .unwrap_or(third)
+            .parse::<f64>()
+            .ok()?;
+        Oklch { l, c: second, h }.to_oklab()
+    } else {
+        let b = parse_number_or_percentage(third, 0.4)?;
+        Oklab { l, a: second, b }
+    };
+    let alpha = match alpha {
+        Some(alpha) => parse_number_or_percentage(alpha, 1.)?,
+        None => 1.,
+    };
+    let mut color = oklab.to_color();
+    color.a = unit_to_u8(alpha);
+    Some(color)
+}
+
+/// `percent_reference` is the value 100% maps to.
+fn parse_number_or_percentage(v: &str, percent_reference: f64) -> Option<f64> {
+    match v.strip_suffix('%') {
+        Some(percentage) => Some(percentage.parse::<f64>().ok()? / 100. * percent_reference),
+        None => v.parse::<f64>().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_colors() -> Vec<Color> {
+        let mut colors = Vec::new();
+        for r in (0..=255).step_by(15) {
+            for g in (0..=255).step_by(17) {
+                for b in (0..=255).step_by(51) {
+                    colors.push(Color::rgb(r as u8, g as u8, b as u8));
+                }
+            }
+        }
+        for a in [0, 1, 64, 127, 128, 200, 254] {
+            colors.push(Color::rgba(12, 200, 99, a));
+        }
+        colors
+    }
+
+    #[test]
+    fn test_formats() {
+        let red = Color::rgb(255, 0, 0);
+        let translucent = Color::rgba(0x12, 0x34, 0x56, 0x80);
+        assert_eq!("#ff0000", red.to_string());
+        assert_eq!("#12345680", translucent.to_string());
+        assert_eq!("ff0000", format!("{red:x}"));
+        assert_eq!("#ff0000", format!("{red:#x}"));
+        assert_eq!("12345680", format!("{translucent:x}"));
+        assert_eq!("#12345680", format!("{translucent:#X}"));
+        assert_eq!("#FF0000", format!("{red:#X}"));
+        assert_eq!("#f00", red.to_hex_short());
+        assert_eq!("#f008", Color::rgba(255, 0, 0, 0x88).to_hex_short());
+        assert_eq!("#12345680", translucent.to_hex_short());
+        assert_eq!("rgb(255 0 0)", red.to_rgb_css());
+        assert_eq!("rgb(18 52 86 / 0.502)", translucent.to_rgb_css());
+        assert_eq!("rgb(255, 0, 0)", red.to_rgb_legacy_css());
+        assert_eq!("rgba(18, 52, 86, 0.502)", translucent.to_rgb_legacy_css());
+        assert_eq!("hsl(0 100% 50%)", red.to_hsl_css());
+        assert_eq!("hsl(210 65.38% 20.39% / 0.502)", translucent.to_hsl_css());
+        assert_eq!("oklch(62.796% 0.25768 29.234)", red.to_oklch_css());
+        assert_eq!("oklch(100% 0 0)", Color::rgb(255, 255, 255).to_oklch_css());
+    }
+
+    #[test]
+    fn test_parses_css_colors() {
+        let parse = |css: &str| css.parse::<Color>().unwrap();
+        assert_eq!(Color::rgb(255, 0, 0), parse("#f00"));
+        assert_eq!(Color::rgb(255, 0, 0), parse(" Red "));
+        assert_eq!(Color::rgba(0, 0, 0, 0), parse("transparent"));
+        assert_eq!(Color::rgb(255, 0, 0), parse("rgb(255, 0, 0)"));
+        assert_eq!(Color::rgba(255, 0, 0, 128), parse("rgb(255 0 0 / 50%)"));
+        assert_eq!(Color::rgb(255, 0, 0), parse("hsl(0 100% 50%)"));
+        assert_eq!(Color::rgb(255, 0, 0), parse("oklch(62.8% 0.2577 29.23)"));
+        assert_eq!(Color::rgb(255, 0, 0), parse("oklch(0.628 0.2577 29.23deg)"));
+        assert_eq!(Color::rgba(255, 255, 255, 128), parse("oklab(1 0 0 / 0.5)"));
+        assert_eq!(
+            Err(ParseColorError(String::from("rgb(1, 2"))),
+            "rgb(1, 2".parse::<Color>()
+        );
+        assert!("oklch(1 2)".parse::<Color>().is_err());
+        assert!("notacolor".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn test_round_trips() {
+        type Formatter = fn(&Color) -> String;
+        let formatters: [(&str, Formatter); 7] = [
+            ("display", |c| c.to_string()),
+            ("hex", Color::to_hex),
+            ("short hex", Color::to_hex_short),
+            ("rgb", Color::to_rgb_css),
+            ("legacy rgb", Color::to_rgb_legacy_css),
+            ("hsl", Color::to_hsl_css),
+            ("oklch", Color::to_oklch_css),
+        ];
+        for color in sample_colors() {
+            for (name, format) in formatters {
+                let css = format(&color);
+                assert_eq!(Ok(color), css.parse::<Color>(), "{name} {css}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parsed_colors_match_scraped_colors() {
+        for css in [
+            "rgba(0,0,0,.5)",
+            "rgb(1% 11% 100% / .5)",
+            "hsl(150 70% 20% / .5)",
+            "hwb(194 0% 0%)",
+        ] {
+            let scraped = crate::css::extract_colors(css);
+            assert_eq!(Ok(scraped[0]), css.parse::<Color>(), "{css}");
+        }
+    }
+}
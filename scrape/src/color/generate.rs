@@ -0,0 +1,225 @@
+use crate::color::{Color, Oklch};
+
+/// Steps of a Tailwind-style lightness ramp.
+pub const RAMP_STEPS: [u16; 11] = [50, 100, 200, 300, 400, 500, 600, 700, 800, 900, 950];
+
+// OKLCH lightness and chroma relative to step 500 of each step, following Tailwind's
+// default palette
+const RAMP_LIGHTNESS: [f64; 11] = [
+    0.97, 0.935, 0.885, 0.81, 0.705, 0.63, 0.56, 0.495, 0.435, 0.385, 0.27,
+];
+const RAMP_CHROMA: [f64; 11] = [0.06, 0.14, 0.26, 0.48, 0.81, 1., 1.03, 0.9, 0.75, 0.6, 0.39];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RampStep {
+    pub step: u16,
+    pub color: Color,
+}
+
+/// Color schemes of hues rotated around the OKLCH hue circle.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Harmony {
+    Complementary,
+    Analogous,
+    Triadic,
+    SplitComplementary,
+    /// Two complementary pairs 60° apart.
+    Tetradic,
+    /// Two complementary pairs 90° apart.
+    Square,
+}
+
+impl Harmony {
+    fn hue_offsets(&self) -> &'static [f64] {
+        match self {
+            Harmony::Complementary => &[0., 180.],
+            Harmony::Analogous => &[0., -30., 30.],
+            Harmony::Triadic => &[0., 120., 240.],
+            Harmony::SplitComplementary => &[0., 150., 210.],
+            Harmony::Tetradic => &[0., 60., 180., 240.],
+            Harmony::Square => &[0., 90., 180., 270.],
+        }
+    }
+}
+
+impl Color {
+    /// Lightness ramp from step 50 to 950 in OKLCH. The color itself is kept at the step
+    /// nearest its lightness and the other steps share its hue, with chroma reduced to
+    /// stay in the sRGB gamut.
+    pub fn ramp(&self) -> Vec<RampStep> {
+        let base = self.to_oklch();
+        let anchor = (0..RAMP_STEPS.len())
+            .min_by(|a, b| {
+                (RAMP_LIGHTNESS[*a] - base.l)
+                    .abs()
+                    .total_cmp(&(RAMP_LIGHTNESS[*b] - base.l).abs())
+            })
+            .unwrap();
+        RAMP_STEPS
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                let color = if i == anchor {
+                    *self
+                } else {
+                    let c = base.c * RAMP_CHROMA[i] / RAMP_CHROMA[anchor];
+                    self.generated(Oklch {
+                        l: RAMP_LIGHTNESS[i],
+                        c,
+                        h: base.h,
+                    })
+                };
+                RampStep { step: *step, color }
+            })
+            .collect()
+    }
+
+    /// `count` progressively lighter colors, mixing towards white.
+    pub fn tints(&self, count: usize) -> Vec<Color> {
+        self.mix_steps(count, |lch, t| Oklch {
+            l: lch.l + (1. - lch.l) * t,
+            c: lch.c * (1. - t),
+            h: lch.h,
+        })
+    }
+
+    /// `count` progressively darker colors, mixing towards black.
+    pub fn shades(&self, count: usize) -> Vec<Color> {
+        self.mix_steps(count, |lch, t| Oklch {
+            l: lch.l * (1. - t),
+            c: lch.c * (1. - t),
+            h: lch.h,
+        })
+    }
+
+    /// `count` progressively less chromatic colors of the same lightness, mixing towards
+    /// gray.
+    pub fn tones(&self, count: usize) -> Vec<Color> {
+        self.mix_steps(count, |lch, t| Oklch {
+            c: lch.c * (1. - t),
+            ..lch
+        })
+    }
+
+    /// The color followed by the other colors of the scheme, keeping its lightness and
+    /// chroma where the gamut allows.
+    pub fn harmony(&self, harmony: Harmony) -> Vec<Color> {
+        let base = self.to_oklch();
+        harmony
+            .hue_offsets()
+            .iter()
+            .map(|offset| match offset {
+                0. => *self,
+                _ => self.generated(Oklch {
+                    h: (base.h + offset).rem_euclid(360.),
+                    ..base
+                }),
+            })
+            .collect()
+    }
+
+    fn mix_steps(&self, count: usize, mix: impl Fn(Oklch, f64) -> Oklch) -> Vec<Color> {
+        let base = self.to_oklch();
+        (1..=count)
+            .map(|i| self.generated(mix(base, i as f64 / (count + 1) as f64)))
+            .collect()
+    }
+
+    fn generated(&self, oklch: Oklch) -> Color {
+        let mut color = oklch.to_color_in_gamut();
+        color.a = self.a;
+        color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hue_difference(a: f64, b: f64) -> f64 {
+        let d = (a - b).rem_euclid(360.);
+        d.min(360. - d)
+    }
+
+    #[test]
+    fn test_ramp() {
+        let blue = Color::rgb(0x3b, 0x82, 0xf6);
+        let ramp = blue.ramp();
+        assert_eq!(
+            RAMP_STEPS.to_vec(),
+            ramp.iter().map(|s| s.step).collect::<Vec<_>>()
+        );
+        assert_eq!(blue, ramp[5].color);
+        let lightness: Vec<f64> = ramp.iter().map(|s| s.color.to_oklch().l).collect();
+        assert!(lightness.windows(2).all(|w| w[0] > w[1]), "{lightness:?}");
+        let hue = blue.to_oklch().h;
+        for step in &ramp[1..] {
+            assert!(
+                hue_difference(hue, step.color.to_oklch().h) < 3.,
+                "{step:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_ramp_anchors_at_nearest_lightness() {
+        let light = Color::rgb(0xfe, 0xca, 0xca);
+        let ramp = light.ramp();
+        assert_eq!(
+            RampStep {
+                step: 200,
+                color: light
+            },
+            ramp[2]
+        );
+        let yellow = Color::rgb(255, 255, 0);
+        let hue = yellow.to_oklch().h;
+        for step in yellow.ramp() {
+            assert!(
+                hue_difference(hue, step.color.to_oklch().h) < 3.,
+                "{step:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tints_shades_and_tones() {
+        let color = Color::rgba(0x33, 0x66, 0x99, 0x80);
+        let l = color.to_oklch().l;
+        let tints = color.tints(4);
+        assert_eq!(4, tints.len());
+        assert!(tints.iter().all(|c| c.to_oklch().l > l && c.a == 0x80));
+        assert!(tints
+            .windows(2)
+            .all(|w| w[0].to_oklch().l < w[1].to_oklch().l));
+        let shades = color.shades(3);
+        assert!(shades
+            .windows(2)
+            .all(|w| w[0].to_oklch().l > w[1].to_oklch().l));
+        assert!(shades[2].to_oklch().l > 0.);
+        let tones = color.tones(3);
+        assert!(tones
+            .windows(2)
+            .all(|w| w[0].to_oklch().c > w[1].to_oklch().c));
+        assert!(tones.iter().all(|c| (c.to_oklch().l - l).abs() < 0.01));
+        assert!(color.tints(0).is_empty());
+    }
+
+    #[test]
+    fn test_harmonies() {
+        let red = Color::rgb(0xcc, 0x33, 0x33);
+        let hue = red.to_oklch().h;
+        let complementary = red.harmony(Harmony::Complementary);
+        assert_eq!(red, complementary[0]);
+        assert!(hue_difference(hue + 180., complementary[1].to_oklch().h) < 2.);
+        let triadic = red.harmony(Harmony::Triadic);
+        assert_eq!(3, triadic.len());
+        assert!(hue_difference(hue + 240., triadic[2].to_oklch().h) < 2.);
+        assert_eq!(3, red.harmony(Harmony::Analogous).len());
+        assert_eq!(3, red.harmony(Harmony::SplitComplementary).len());
+        assert_eq!(4, red.harmony(Harmony::Tetradic).len());
+        assert_eq!(4, red.harmony(Harmony::Square).len());
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use thiserror::*;
+
+use crate::color::{Color, DifferenceMetric};
+use crate::css::{keyword_colors, parse_hex_color};
+
+/// A set of named colors to look up colors by name or find the name nearest to a color.
+#[derive(Clone, Debug)]
+pub struct ColorNames {
+    entries: Vec<(String, Color)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NamedColor {
+    pub name: String,
+    pub color: Color,
+    /// Difference between the looked up color and `color`.
+    pub distance: f64,
+}
+
+#[derive(Error, Debug)]
+pub enum ColorNamesError {
+    #[error("error reading color names: {0}")]
+    Read(#[from] std::io::Error),
+    #[error("invalid color name entry on line {0}")]
+    InvalidEntry(usize),
+}
+
+impl ColorNames {
+    pub fn new(entries: impl IntoIterator<Item = (String, Color)>) -> Self {
+        Self {
+            entries: entries.into_iter().collect(),
+        }
+    }
+
+    /// The named color keywords of CSS, where aliases such as `aqua` and `cyan` resolve
+    /// to the alphabetically first name.
+    pub fn css() -> Self {
+        Self::new(
+            keyword_colors()
+                .into_iter()
+                .map(|(name, color)| (name.to_string(), color)),
+        )
+    }
+
+    /// Parses one entry per line of a name followed by a hex color, separated by
+    /// whitespace or a comma, as in the XKCD color survey's `rgb.txt`. Blank lines and
+    /// lines starting with `#` or `//` are skipped.
+    pub fn parse(text: &str) -> Result<Self, ColorNamesError> {
+        let mut entries = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+                continue;
+            }
+            let (name, hex) = line
+                .rsplit_once(|c: char| c.is_whitespace() || c == ',')
+                .ok_or(ColorNamesError::InvalidEntry(i + 1))?;
+            let name = name.trim().trim_end_matches(',').trim();
+            let color = parse_hex_color(hex.trim()).ok_or(ColorNamesError::InvalidEntry(i + 1))?;
+            if name.is_empty() {
+                return Err(ColorNamesError::InvalidEntry(i + 1));
+            }
+            entries.push((name.to_string(), color));
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ColorNamesError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Color of the first entry with `name`, ignoring ASCII case.
+    pub fn get(&self, name: &str) -> Option<Color> {
+        self.entries
+            .iter()
+            .find(|(entry_name, _)| entry_name.eq_ignore_ascii_case(name))
+            .map(|(_, color)| *color)
+    }
+
+    /// Nearest named color by CIEDE2000.
+    pub fn nearest(&self, color: &Color) -> Option<NamedColor> {
+        self.nearest_by(color, DifferenceMetric::Ciede2000)
+    }
+
+    pub fn nearest_by(&self, color: &Color, metric: DifferenceMetric) -> Option<NamedColor> {
+        self.entries
+            .iter()
+            .map(|(name, named)| (name, named, color.difference(named, metric)))
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(name, named, distance)| NamedColor {
+                name: name.clone(),
+                color: *named,
+                distance,
+            })
+    }
+}
+
+impl Color {
+    pub fn nearest_css_name(&self) -> NamedColor {
+        ColorNames::css().nearest(self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_css_name() {
+        let exact = Color::rgb(0x66, 0x33, 0x99).nearest_css_name();
+        assert_eq!("rebeccapurple", exact.name);
+        assert_eq!(0., exact.distance);
+        let near = Color::rgb(0xfe, 0x01, 0x00).nearest_css_name();
+        assert_eq!("red", near.name);
+        assert_eq!(Color::rgb(255, 0, 0), near.color);
+        assert!(near.distance > 0. && near.distance < 1.);
+        assert_eq!("aqua", Color::rgb(0, 255, 255).nearest_css_name().name);
+    }
+
+    #[test]
+    fn test_reverse_lookup() {
+        let names = ColorNames::css();
+        assert_eq!(148, names.len());
+        assert_eq!(
+            Some(Color::rgb(0x66, 0x33, 0x99)),
+            names.get("RebeccaPurple")
+        );
+        assert_eq!(None, names.get("brand-blue"));
+    }
+
+    #[test]
+    fn test_parse_color_names() {
+        let names = ColorNames::parse(
+            "# License: http://creativecommons.org/publicdomain/zero/1.0/\n\
+             cloudy blue\t#acc2d9\n\
+             \n\
+             dark pastel green\t#56ae57\n\
+             Brand Red, #e4002b\n",
+        )
+        .unwrap();
+        assert_eq!(3, names.len());
+        assert_eq!(Some(Color::rgb(0xe4, 0x00, 0x2b)), names.get("brand red"));
+        let nearest = names.nearest(&Color::rgb(0x57, 0xad, 0x57)).unwrap();
+        assert_eq!("dark pastel green", nearest.name);
+        assert!(matches!(
+            ColorNames::parse("cloudy blue\t#acc2d9\nnot a color"),
+            Err(ColorNamesError::InvalidEntry(2))
+        ));
+    }
+}
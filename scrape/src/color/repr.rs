@@ -0,0 +1,254 @@
+//! Serde representations of [`Color`]. Colors serialize as hex strings by default, the
+//! `hex`, `rgba` and `css` modules select a representation with `#[serde(with = "...")]`,
+//! and [`WithColorRepr`] selects one for every color of a value such as [`SiteColors`].
+//! Deserializing accepts every representation.
+//!
+//! [`SiteColors`]: crate::SiteColors
+
+use std::cell::Cell;
+use std::fmt;
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::color::Color;
+
+/// How colors without a `#[serde(with = "...")]` attribute serialize.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ColorRepr {
+    #[default]
+    Hex,
+    Rgba,
+    Css,
+}
+
+thread_local! {
+    static COLOR_REPR: Cell<ColorRepr> = const { Cell::new(ColorRepr::Hex) };
+}
+
+/// Serializes a value with its colors in the given representation.
+///
+/// ```
+/// # use color_scrape::color::repr::{ColorRepr, WithColorRepr};
+/// # use color_scrape::SiteColors;
+/// let site = SiteColors::default();
+/// let json = serde_json::to_string(&WithColorRepr(&site, ColorRepr::Css)).unwrap();
+/// ```
+pub struct WithColorRepr<'a, T: ?Sized>(pub &'a T, pub ColorRepr);
+
+impl<T: Serialize + ?Sized> Serialize for WithColorRepr<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        /// Restores the enclosing representation, also when serializing panics.
+        struct Restore(ColorRepr);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                COLOR_REPR.with(|repr| repr.set(self.0));
+            }
+        }
+
+        let _restore = Restore(COLOR_REPR.with(|repr| repr.replace(self.1)));
+        self.0.serialize(serializer)
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match COLOR_REPR.with(Cell::get) {
+            ColorRepr::Hex => hex::serialize(self, serializer),
+            ColorRepr::Rgba => rgba::serialize(self, serializer),
+            ColorRepr::Css => css::serialize(self, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ColorVisitor)
+    }
+}
+
+/// `"#rrggbb"` or `"#rrggbbaa"`.
+pub mod hex {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&color.to_hex())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        Color::deserialize(deserializer)
+    }
+}
+
+/// `{"r": 255, "g": 0, "b": 0, "a": 255}`, where `a` defaults to 255 when deserializing.
+pub mod rgba {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Rgba {
+        r: u8,
+        g: u8,
+        b: u8,
+        a: u8,
+    }
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        Rgba {
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            a: color.a,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        Color::deserialize(deserializer)
+    }
+}
+
+/// `"rgb(255 0 0)"` or `"rgb(255 0 0 / 0.5)"`.
+pub mod css {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&color.to_rgb_css())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        Color::deserialize(deserializer)
+    }
+}
+
+struct ColorVisitor;
+
+impl<'de> Visitor<'de> for ColorVisitor {
+    type Value = Color;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a CSS color string or an object of r, g, b and a channels")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Color, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Color, A::Error> {
+        let (mut r, mut g, mut b, mut a) = (None, None, None, None);
+        while let Some(key) = map.next_key::<String>()? {
+            let channel = match key.as_str() {
+                "r" => &mut r,
+                "g" => &mut g,
+                "b" => &mut b,
+                "a" => &mut a,
+                _ => return Err(de::Error::unknown_field(&key, &["r", "g", "b", "a"])),
+            };
+            *channel = Some(map.next_value::<u8>()?);
+        }
+        Ok(Color::rgba(
+            r.ok_or_else(|| de::Error::missing_field("r"))?,
+            g.ok_or_else(|| de::Error::missing_field("g"))?,
+            b.ok_or_else(|| de::Error::missing_field("b"))?,
+            a.unwrap_or(255),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColorPair, SiteColors};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Representations {
+        default: Color,
+        #[serde(with = "rgba")]
+        rgba: Color,
+        #[serde(with = "css")]
+        css: Color,
+    }
+
+    #[test]
+    fn test_color_representations() {
+        let color = Color::rgba(255, 0, 0, 128);
+        let json = serde_json::to_string(&Representations {
+            default: color,
+            rgba: color,
+            css: color,
+        })
+        .unwrap();
+        assert_eq!(
+            r##"{"default":"#ff000080","rgba":{"r":255,"g":0,"b":0,"a":128},"css":"rgb(255 0 0 / 0.502)"}"##,
+            json
+        );
+        let parsed: Representations = serde_json::from_str(&json).unwrap();
+        assert_eq!(color, parsed.default);
+        assert_eq!(color, parsed.rgba);
+        assert_eq!(color, parsed.css);
+    }
+
+    #[test]
+    fn test_deserializes_any_representation() {
+        let colors: Vec<Color> =
+            serde_json::from_str(r#"["red", "hsl(0 100% 50%)", {"r": 255, "g": 0, "b": 0}]"#)
+                .unwrap();
+        assert!(colors.iter().all(|c| *c == Color::rgb(255, 0, 0)));
+        assert!(serde_json::from_str::<Color>(r#""nope""#).is_err());
+        assert!(serde_json::from_str::<Color>(r#"{"r": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_site_colors() {
+        let site = SiteColors {
+            colors: vec![Color::rgb(0x11, 0x11, 0x11), Color::rgb(255, 255, 255)],
+            pairs: vec![ColorPair {
+                selector: String::from("body"),
+                foreground: Color::rgb(0x11, 0x11, 0x11),
+                background: Color::rgb(255, 255, 255),
+            }],
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&site).unwrap();
+        assert_eq!(serde_json::json!(["#111111", "#ffffff"]), json["colors"]);
+        assert_eq!("#111111", json["pairs"][0]["foreground"]);
+        let json = json.to_string();
+        let parsed: SiteColors = serde_json::from_str(&json).unwrap();
+        assert_eq!(site.colors, parsed.colors);
+        assert_eq!(site.pairs, parsed.pairs);
+    }
+
+    #[test]
+    fn test_site_colors_with_repr() {
+        let site = SiteColors {
+            colors: vec![Color::rgba(255, 0, 0, 128)],
+            pairs: vec![ColorPair {
+                selector: String::from("body"),
+                foreground: Color::rgb(0x11, 0x11, 0x11),
+                background: Color::rgb(255, 255, 255),
+            }],
+            ..Default::default()
+        };
+        let json = serde_json::to_value(WithColorRepr(&site, ColorRepr::Rgba)).unwrap();
+        assert_eq!(
+            serde_json::json!([{"r": 255, "g": 0, "b": 0, "a": 128}]),
+            json["colors"]
+        );
+        assert_eq!(
+            serde_json::json!({"r": 17, "g": 17, "b": 17, "a": 255}),
+            json["pairs"][0]["foreground"]
+        );
+        let parsed: SiteColors = serde_json::from_value(json).unwrap();
+        assert_eq!(site.colors, parsed.colors);
+        assert_eq!(site.pairs, parsed.pairs);
+
+        let json = serde_json::to_value(WithColorRepr(&site, ColorRepr::Css)).unwrap();
+        assert_eq!("rgb(255 0 0 / 0.502)", json["colors"][0]);
+        assert_eq!("rgb(17 17 17)", json["pairs"][0]["foreground"]);
+        // the representation only lasts for the wrapped value
+        assert_eq!(
+            "#111111",
+            serde_json::to_value(site.pairs[0].foreground).unwrap()
+        );
+    }
+}
@@ -0,0 +1,461 @@
+use crate::color::Color;
+
+// D65 reference white derived from its xy chromaticity, matching CSS Color 4
+const D65: [f64; 3] = [0.3127 / 0.3290, 1.0, (1.0 - 0.3127 - 0.3290) / 0.3290];
+
+const LINEAR_SRGB_TO_XYZ: [[f64; 3]; 3] = [
+    [0.41239079926595934, 0.357584339383878, 0.1804807884018343],
+    [0.21263900587151027, 0.715168678767756, 0.07219231536073371],
+    [0.01933081871559182, 0.11919477979462598, 0.9505321522496607],
+];
+
+const XYZ_TO_LINEAR_SRGB: [[f64; 3]; 3] = [
+    [3.2409699419045226, -1.537383177570094, -0.4986107602930034],
+    [-0.9692436362808796, 1.8759675015077204, 0.04155505740717559],
+    [
+        0.05563007969699366,
+        -0.20397695888897652,
+        1.0569715142428786,
+    ],
+];
+
+const CIE_EPSILON: f64 = 216. / 24389.;
+const CIE_KAPPA: f64 = 24389. / 27.;
+const GAMUT_EPSILON: f64 = 1e-6;
+
+/// CIE XYZ tristimulus values relative to D65, with `y` of reference white at 1.0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Xyz {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// CIE L*a*b* relative to D65.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+/// Polar form of [`Oklab`] with hue in degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklch {
+    pub l: f64,
+    pub c: f64,
+    pub h: f64,
+}
+
+/// CAM16 appearance correlates of lightness, chroma, colorfulness and hue angle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cam16 {
+    pub j: f64,
+    pub c: f64,
+    pub m: f64,
+    pub h: f64,
+}
+
+/// Cartesian coordinates of the CAM16 uniform color space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cam16Ucs {
+    pub j: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+pub(crate) struct ViewingConditions {
+    d_rgb: [f64; 3],
+    f_l: f64,
+    n: f64,
+    z: f64,
+    n_bb: f64,
+    n_c: f64,
+    c: f64,
+    a_w: f64,
+}
+
+const M16: [[f64; 3]; 3] = [
+    [0.401288, 0.650173, -0.051461],
+    [-0.250268, 1.204414, 0.045854],
+    [-0.002079, 0.048952, 0.953127],
+];
+
+impl ViewingConditions {
+    /// `white` is scaled so `Y` is 100, `adapting_luminance` is in cd/m² and surround is
+    /// given as the `F`, `c` and `N_c` triple from CIECAM02.
+    pub(crate) fn new(
+        white: [f64; 3],
+        adapting_luminance: f64,
+        background_luminance: f64,
+        surround: (f64, f64, f64),
+    ) -> Self {
+        let (f, c, n_c) = surround;
+        let rgb_w = mul(&M16, white);
+        let d = (f * (1. - (1. / 3.6) * ((-adapting_luminance - 42.) / 92.).exp())).clamp(0., 1.);
+        let d_rgb = rgb_w.map(|v| d * white[1] / v + 1. - d);
+        let k = 1. / (5. * adapting_luminance + 1.);
+        let k4 = k.powi(4);
+        let f_l = 0.2 * k4 * (5. * adapting_luminance)
+            + 0.1 * (1. - k4).powi(2) * (5. * adapting_luminance).cbrt();
+        let n = background_luminance / white[1];
+        let z = 1.48 + n.sqrt();
+        let n_bb = 0.725 * n.powf(-0.2);
+        let rgb_aw = [0, 1, 2].map(|i| adapt_response(d_rgb[i] * rgb_w[i], f_l));
+        let a_w = (2. * rgb_aw[0] + rgb_aw[1] + 0.05 * rgb_aw[2] - 0.305) * n_bb;
+        Self {
+            d_rgb,
+            f_l,
+            n,
+            z,
+            n_bb,
+            n_c,
+            c,
+            a_w,
+        }
+    }
+
+    /// sRGB viewing conditions of a 64 lux ambient with a 20% gray background.
+    pub(crate) fn srgb() -> Self {
+        Self::new(
+            D65.map(|v| v * 100.),
+            64. / std::f64::consts::PI * 0.2,
+            20.,
+            (1.0, 0.69, 1.0),
+        )
+    }
+
+    /// `xyz` is scaled so `Y` of the reference white is 100.
+    pub(crate) fn cam16(&self, xyz: [f64; 3]) -> Cam16 {
+        let rgb = mul(&M16, xyz);
+        let [r_a, g_a, b_a] = [0, 1, 2].map(|i| adapt_response(self.d_rgb[i] * rgb[i], self.f_l));
+        let a = r_a - 12. * g_a / 11. + b_a / 11.;
+        let b = (r_a + g_a - 2. * b_a) / 9.;
+        let h = hue_degrees(b, a);
+        let e_t = 0.25 * ((h.to_radians() + 2.).cos() + 3.8);
+        let achromatic = (2. * r_a + g_a + 0.05 * b_a - 0.305) * self.n_bb;
+        let j = 100. * (achromatic / self.a_w).max(0.).powf(self.c * self.z);
+        let t = (50000. / 13. * self.n_c * self.n_bb * e_t * a.hypot(b))
+            / (r_a + g_a + 21. / 20. * b_a);
+        let c = t.powf(0.9) * (j / 100.).sqrt() * (1.64 - 0.29_f64.powf(self.n)).powf(0.73);
+        let m = c * self.f_l.powf(0.25);
+        Cam16 { j, c, m, h }
+    }
+}
+
+fn adapt_response(component: f64, f_l: f64) -> f64 {
+    let p = (f_l * component.abs() / 100.).powf(0.42);
+    component.signum() * 400. * p / (p + 27.13) + 0.1
+}
+
+fn mul(m: &[[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+fn hue_degrees(y: f64, x: f64) -> f64 {
+    let h = y.atan2(x).to_degrees();
+    if h < 0. {
+        h + 360.
+    } else {
+        h
+    }
+}
+
+pub(crate) fn srgb_to_linear(channel: u8) -> f64 {
+    let c = channel as f64 / 255.;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub(crate) fn linear_to_srgb(channel: f64) -> f64 {
+    if channel <= 0.0031308 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1. / 2.4) - 0.055
+    }
+}
+
+fn unit_to_u8(v: f64) -> u8 {
+    (v.clamp(0., 1.) * 255.).round() as u8
+}
+
+impl Color {
+    pub(crate) fn linear_rgb(&self) -> [f64; 3] {
+        [
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+        ]
+    }
+
+    /// Builds a color from linear sRGB components, clipping anything out of gamut.
+    pub(crate) fn from_linear_rgb(rgb: [f64; 3], a: u8) -> Self {
+        let [r, g, b] = rgb.map(|c| unit_to_u8(linear_to_srgb(c)));
+        Color::rgba(r, g, b, a)
+    }
+
+    pub fn to_xyz(&self) -> Xyz {
+        let [x, y, z] = mul(&LINEAR_SRGB_TO_XYZ, self.linear_rgb());
+        Xyz { x, y, z }
+    }
+
+    pub fn to_lab(&self) -> Lab {
+        self.to_xyz().to_lab()
+    }
+
+    pub fn to_oklab(&self) -> Oklab {
+        Oklab::from_linear_rgb(self.linear_rgb())
+    }
+
+    pub fn to_oklch(&self) -> Oklch {
+        self.to_oklab().to_oklch()
+    }
+
+    /// CAM16 correlates under sRGB viewing conditions.
+    pub fn to_cam16(&self) -> Cam16 {
+        let xyz = self.to_xyz();
+        ViewingConditions::srgb().cam16([xyz.x * 100., xyz.y * 100., xyz.z * 100.])
+    }
+
+    pub fn to_cam16_ucs(&self) -> Cam16Ucs {
+        self.to_cam16().to_ucs()
+    }
+}
+
+impl Xyz {
+    pub fn to_lab(&self) -> Lab {
+        let f = |t: f64| {
+            if t > CIE_EPSILON {
+                t.cbrt()
+            } else {
+                (CIE_KAPPA * t + 16.) / 116.
+            }
+        };
+        let fx = f(self.x / D65[0]);
+        let fy = f(self.y / D65[1]);
+        let fz = f(self.z / D65[2]);
+        Lab {
+            l: 116. * fy - 16.,
+            a: 500. * (fx - fy),
+            b: 200. * (fy - fz),
+        }
+    }
+
+    pub fn to_linear_rgb(&self) -> [f64; 3] {
+        mul(&XYZ_TO_LINEAR_SRGB, [self.x, self.y, self.z])
+    }
+}
+
+impl Lab {
+    pub fn to_xyz(&self) -> Xyz {
+        let fy = (self.l + 16.) / 116.;
+        let fx = self.a / 500. + fy;
+        let fz = fy - self.b / 200.;
+        let f_inv = |t: f64| {
+            if t.powi(3) > CIE_EPSILON {
+                t.powi(3)
+            } else {
+                (116. * t - 16.) / CIE_KAPPA
+            }
+        };
+        let y = if self.l > CIE_KAPPA * CIE_EPSILON {
+            fy.powi(3)
+        } else {
+            self.l / CIE_KAPPA
+        };
+        Xyz {
+            x: f_inv(fx) * D65[0],
+            y: y * D65[1],
+            z: f_inv(fz) * D65[2],
+        }
+    }
+
+    pub fn chroma(&self) -> f64 {
+        self.a.hypot(self.b)
+    }
+}
+
+impl Oklab {
+    pub(crate) fn from_linear_rgb([r, g, b]: [f64; 3]) -> Self {
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+        Oklab {
+            l: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        }
+    }
+
+    pub fn to_linear_rgb(&self) -> [f64; 3] {
+        let l = (self.l + 0.3963377774 * self.a + 0.2158037573 * self.b).powi(3);
+        let m = (self.l - 0.1055613458 * self.a - 0.0638541728 * self.b).powi(3);
+        let s = (self.l - 0.0894841775 * self.a - 1.2914855480 * self.b).powi(3);
+        [
+            4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+            -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+            -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+        ]
+    }
+
+    /// Converts to an opaque sRGB color, clipping anything out of gamut.
+    pub fn to_color(&self) -> Color {
+        Color::from_linear_rgb(self.to_linear_rgb(), 255)
+    }
+
+    pub fn to_oklch(&self) -> Oklch {
+        Oklch {
+            l: self.l,
+            c: self.a.hypot(self.b),
+            h: hue_degrees(self.b, self.a),
+        }
+    }
+}
+
+impl Oklch {
+    pub fn to_oklab(&self) -> Oklab {
+        let h = self.h.to_radians();
+        Oklab {
+            l: self.l,
+            a: self.c * h.cos(),
+            b: self.c * h.sin(),
+        }
+    }
+
+    pub fn to_color(&self) -> Color {
+        self.to_oklab().to_color()
+    }
+
+    pub fn in_gamut(&self) -> bool {
+        self.to_oklab()
+            .to_linear_rgb()
+            .iter()
+            .all(|c| (-GAMUT_EPSILON..=1. + GAMUT_EPSILON).contains(c))
+    }
+
+    /// Converts to an opaque sRGB color, reducing chroma until the color is in gamut so
+    /// lightness and hue are preserved.
+    pub fn to_color_in_gamut(&self) -> Color {
+        if self.l >= 1. {
+            return Color::rgb(255, 255, 255);
+        } else if self.l <= 0. {
+            return Color::rgb(0, 0, 0);
+        } else if self.in_gamut() {
+            return self.to_color();
+        }
+        let (mut low, mut high) = (0., self.c);
+        for _ in 0..24 {
+            let c = (low + high) / 2.;
+            if (Oklch { c, ..*self }).in_gamut() {
+                low = c;
+            } else {
+                high = c;
+            }
+        }
+        Oklch { c: low, ..*self }.to_color()
+    }
+}
+
+impl Cam16 {
+    pub fn to_ucs(&self) -> Cam16Ucs {
+        let m = (1. + 0.0228 * self.m).ln() / 0.0228;
+        let h = self.h.to_radians();
+        Cam16Ucs {
+            j: 1.7 * self.j / (1. + 0.007 * self.j),
+            a: m * h.cos(),
+            b: m * h.sin(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+        assert!(
+            (expected - actual).abs() <= tolerance,
+            "expected {expected} but was {actual}"
+        );
+    }
+
+    #[test]
+    fn test_lab_conversion() {
+        let white = Color::rgb(255, 255, 255).to_lab();
+        assert_close(100., white.l, 1e-9);
+        assert_close(0., white.a, 1e-9);
+        assert_close(0., white.b, 1e-9);
+        let red = Color::rgb(255, 0, 0).to_lab();
+        assert_close(53.24, red.l, 1e-2);
+        assert_close(80.09, red.a, 1e-2);
+        assert_close(67.20, red.b, 1e-2);
+        let xyz = red.to_xyz();
+        assert_close(0.412391, xyz.x, 1e-6);
+        assert_close(0.212639, xyz.y, 1e-6);
+        assert_close(0.019331, xyz.z, 1e-6);
+    }
+
+    #[test]
+    fn test_oklab_conversion() {
+        let cases = [
+            (Color::rgb(255, 255, 255), [1., 0., 0.]),
+            (Color::rgb(255, 0, 0), [0.627955, 0.224863, 0.125846]),
+            (Color::rgb(0, 255, 0), [0.866440, -0.233888, 0.179498]),
+            (Color::rgb(0, 0, 255), [0.452014, -0.032457, -0.311528]),
+        ];
+        for (color, [l, a, b]) in cases {
+            let oklab = color.to_oklab();
+            assert_close(l, oklab.l, 1e-5);
+            assert_close(a, oklab.a, 1e-5);
+            assert_close(b, oklab.b, 1e-5);
+            assert_eq!(color, oklab.to_color());
+        }
+    }
+
+    #[test]
+    fn test_gamut_mapping() {
+        let red = Color::rgb(255, 0, 0).to_oklch();
+        assert!(red.in_gamut());
+        assert_eq!(Color::rgb(255, 0, 0), red.to_color_in_gamut());
+        let vivid = Oklch {
+            l: 0.7,
+            c: 0.4,
+            h: 150.,
+        };
+        assert!(!vivid.in_gamut());
+        let mapped = vivid.to_color_in_gamut().to_oklch();
+        assert_close(0.7, mapped.l, 5e-3);
+        assert_close(150., mapped.h, 1.);
+        assert!(mapped.c < 0.4);
+        assert_eq!(
+            Color::rgb(255, 255, 255),
+            Oklch { l: 1.2, ..vivid }.to_color_in_gamut()
+        );
+    }
+
+    #[test]
+    fn test_cam16_reference() {
+        let vc = ViewingConditions::new([95.05, 100., 108.88], 318.31, 20., (1.0, 0.69, 1.0));
+        let cam16 = vc.cam16([19.01, 20.00, 21.78]);
+        assert_close(41.731208, cam16.j, 1e-4);
+        assert_close(0.103356, cam16.c, 1e-4);
+        assert_close(217.067960, cam16.h, 1e-2);
+        assert_close(0.107437, cam16.m, 1e-4);
+    }
+}
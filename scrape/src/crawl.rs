@@ -0,0 +1,195 @@
+use std::collections::{HashSet, VecDeque};
+
+use futures_util::future::join_all;
+use reqwest::Url;
+
+use crate::crawl::sitemap::{sample, sitemap_pages};
+use crate::fetch::{origin, Fetcher};
+use crate::html::search_links;
+use crate::{scrape_sources, ColorScrapeError, ScrapeOptions, SiteColors};
+
+pub use self::sitemap::SitemapSampling;
+
+mod sitemap;
+
+/// Extensions of links that are followed, besides paths without an extension.
+const PAGE_EXTENSIONS: [&str; 6] = ["html", "htm", "xhtml", "php", "asp", "aspx"];
+
+#[derive(Clone, Debug)]
+pub struct CrawlOptions {
+    /// Number of links followed from the start page, 0 to scrape only the start page.
+    pub max_depth: usize,
+    /// Number of pages scraped, including the start page.
+    pub max_pages: usize,
+    /// Also visits pages listed by the site's sitemaps, picked with the given sampling,
+    /// before following links.
+    pub sitemap: Option<SitemapSampling>,
+    pub scrape: ScrapeOptions,
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 2,
+            max_pages: 10,
+            sitemap: None,
+            scrape: ScrapeOptions::default(),
+        }
+    }
+}
+
+/// Colors of the pages of a site.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrawledSite {
+    /// Colors of every page, with the sources pages share counted once.
+    pub site: SiteColors,
+    /// Colors of each page in the order crawled.
+    pub pages: Vec<SiteColors>,
+}
+
+/// Scrapes the page at `url` and the same-origin pages it links to, breadth-first, as many
+/// at once as the fetch options allow per host. Pages other than the start page that fail
+/// to load are skipped.
+pub async fn crawl(url: String, options: &CrawlOptions) -> Result<CrawledSite, ColorScrapeError> {
+    let fetcher = Fetcher::new(&options.scrape.fetch)?;
+    let mut crawled = CrawledSite {
+        site: SiteColors {
+            url: url.clone(),
+            ..Default::default()
+        },
+        pages: Vec::new(),
+    };
+    let mut seen_sources: HashSet<String> = HashSet::new();
+    // pages are queued by normalized URL, so that differently spelled links to a page
+    // don't visit it again
+    let mut queued: HashSet<String> = HashSet::from([normalize_url(&url)]);
+    let mut queue = VecDeque::from([(url.clone(), 0)]);
+    if let Some(sampling) = options.sitemap {
+        let pages: Vec<String> = sitemap_pages(&fetcher, origin(&url))
+            .await
+            .into_iter()
+            .map(|page| normalize_url(&page))
+            .filter(|page| same_origin(page, &url) && !queued.contains(page))
+            .collect();
+        let count = options.max_pages.saturating_sub(1);
+        for page in sample(pages, count, sampling) {
+            queued.insert(page.clone());
+            queue.push_back((page, 1));
+        }
+    }
+    let concurrency = options.scrape.fetch.max_concurrent_per_host.max(1);
+    while !queue.is_empty() && crawled.pages.len() < options.max_pages {
+        let batch_size = concurrency.min(options.max_pages - crawled.pages.len());
+        let batch: Vec<(String, usize)> =
+            (0..batch_size).filter_map(|_| queue.pop_front()).collect();
+        let results = join_all(
+            batch
+                .iter()
+                .map(|(page_url, _)| scrape_page(page_url, &options.scrape, &fetcher)),
+        )
+        .await;
+        for ((page_url, depth), result) in batch.into_iter().zip(results) {
+            let (html, contributions) = match result {
+                Ok(scraped) => scraped,
+                Err(err) if page_url == url => return Err(err),
+                Err(_) => continue,
+            };
+            let mut page = SiteColors {
+                url: page_url.clone(),
+                ..Default::default()
+            };
+            for (key, contribution) in contributions {
+                if seen_sources.insert(key) {
+                    crawled.site.merge(contribution.clone());
+                }
+                page.merge(contribution);
+            }
+            page.finish(&options.scrape);
+            crawled.pages.push(page);
+
+            if depth < options.max_depth {
+                for link in search_links(&page_url, &html) {
+                    if same_origin(&link, &url) && is_page_url(&link) && queued.insert(link.clone())
+                    {
+                        queue.push_back((link, depth + 1));
+                    }
+                }
+            }
+        }
+    }
+    crawled.site.finish(&options.scrape);
+    Ok(crawled)
+}
+
+/// HTML of a page and the colors of its sources.
+async fn scrape_page(
+    url: &String,
+    options: &ScrapeOptions,
+    fetcher: &Fetcher,
+) -> Result<(String, Vec<(String, SiteColors)>), ColorScrapeError> {
+    let (html, encoding) = fetcher.html(url).await?;
+    let contributions = scrape_sources(url, &html, encoding, options, fetcher).await?;
+    Ok((html, contributions))
+}
+
+/// `url` with a lowercase host, without dot segments and fragment, or as given when it
+/// doesn't parse.
+fn normalize_url(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut parsed) => {
+            parsed.set_fragment(None);
+            parsed.to_string()
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
+fn same_origin(a: &str, b: &str) -> bool {
+    origin(a).eq_ignore_ascii_case(origin(b))
+}
+
+fn is_page_url(url: &str) -> bool {
+    let path = &url[origin(url).len()..];
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    let segment = path.rsplit('/').next().unwrap_or(path);
+    match segment.rsplit_once('.') {
+        Some((_, extension)) => PAGE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_origin() {
+        assert!(same_origin("https://host/a", "https://HOST"));
+        assert!(same_origin("https://host?q", "https://host/b/c"));
+        assert!(!same_origin("https://host:8080/", "https://host/"));
+        assert!(!same_origin("http://host/", "https://host/"));
+        assert!(!same_origin("https://host.evil/", "https://host/"));
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!("https://host/a", normalize_url("https://HOST/./a#top"));
+        assert_eq!("https://host/", normalize_url("https://host"));
+        assert_eq!(
+            "https://host/b?q=1",
+            normalize_url("https://host/a/../b?q=1")
+        );
+        assert_eq!("not a url", normalize_url("not a url"));
+    }
+
+    #[test]
+    fn test_is_page_url() {
+        assert!(is_page_url("https://host"));
+        assert!(is_page_url("https://host/pricing"));
+        assert!(is_page_url("https://host/docs/v1.2/"));
+        assert!(is_page_url("https://host/about.HTML?lang=en"));
+        assert!(!is_page_url("https://host/report.pdf"));
+        assert!(!is_page_url("https://host/img/logo.png"));
+    }
+}
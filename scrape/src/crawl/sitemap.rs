@@ -0,0 +1,265 @@
+use std::collections::HashSet;
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::fetch::Fetcher;
+
+/// Sitemap files read at most, since indexes of large sites list thousands of them.
+const MAX_SITEMAPS: usize = 20;
+/// Size limit of the sitemaps protocol.
+const MAX_SITEMAP_SIZE: usize = 50 * 1024 * 1024;
+
+lazy_static! {
+    static ref ROBOTS_SITEMAP_REGEX: Regex =
+        Regex::new(r"(?im)^\s*sitemap\s*:\s*(?P<url>\S+)").unwrap();
+    static ref SITEMAP_ENTRY_REGEX: Regex = Regex::new(
+        r"(?s)<(?P<kind>sitemap|url)(?:\s[^>]*)?>.*?<loc>\s*(?P<loc>.*?)\s*</loc>.*?</(?:sitemap|url)>"
+    )
+    .unwrap();
+}
+
+/// How pages listed by sitemaps are picked when there are more than a crawl can visit.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SitemapSampling {
+    /// Pages in the order the sitemaps list them.
+    First,
+    /// Pages evenly spaced over the whole list.
+    Spread,
+    /// Pages taken in turn from each section, the first segment of the path, so that
+    /// sections such as `/blog` don't crowd out the rest.
+    #[default]
+    BySection,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Sitemap {
+    /// Nested sitemaps of a sitemap index.
+    sitemaps: Vec<String>,
+    pages: Vec<String>,
+}
+
+/// Pages listed by the sitemaps declared in `robots.txt`, or by `/sitemap.xml` when it
+/// declares none.
+pub(crate) async fn sitemap_pages(fetcher: &Fetcher, origin: &str) -> Vec<String> {
+    let mut queue = match fetcher.text(&format!("{origin}/robots.txt")).await {
+        // reversed, since the queue is taken from the back
+        Ok(robots) => robots_sitemaps(&robots).into_iter().rev().collect(),
+        Err(_) => Vec::new(),
+    };
+    if queue.is_empty() {
+        queue.push(format!("{origin}/sitemap.xml"));
+    }
+    let mut read = Vec::new();
+    let mut pages = Vec::new();
+    let mut seen = HashSet::new();
+    while let Some(url) = queue.pop() {
+        if read.len() >= MAX_SITEMAPS || read.contains(&url) {
+            continue;
+        }
+        let Ok(bytes) = fetcher.bytes(&url, MAX_SITEMAP_SIZE).await else {
+            read.push(url);
+            continue;
+        };
+        let sitemap = parse_sitemap(&decompress(bytes));
+        read.push(url);
+        queue.extend(sitemap.sitemaps.into_iter().rev());
+        for page in sitemap.pages {
+            if seen.insert(page.clone()) {
+                pages.push(page);
+            }
+        }
+    }
+    pages
+}
+
+/// Picks `count` of the `pages`.
+pub(crate) fn sample(pages: Vec<String>, count: usize, sampling: SitemapSampling) -> Vec<String> {
+    if pages.len() <= count {
+        return pages;
+    }
+    if count == 0 {
+        return Vec::new();
+    }
+    match sampling {
+        SitemapSampling::First => pages.into_iter().take(count).collect(),
+        SitemapSampling::Spread => (0..count)
+            .map(|i| pages[i * pages.len() / count].clone())
+            .collect(),
+        SitemapSampling::BySection => {
+            let mut sections: Vec<(&str, Vec<&String>)> = Vec::new();
+            for page in &pages {
+                let section = section(page);
+                match sections.iter_mut().find(|(s, _)| *s == section) {
+                    Some((_, section_pages)) => section_pages.push(page),
+                    None => sections.push((section, vec![page])),
+                }
+            }
+            let mut sampled = Vec::new();
+            for round in 0.. {
+                let before = sampled.len();
+                for (_, section_pages) in &sections {
+                    if let Some(page) = section_pages.get(round) {
+                        sampled.push((*page).clone());
+                        if sampled.len() == count {
+                            return sampled;
+                        }
+                    }
+                }
+                if sampled.len() == before {
+                    break;
+                }
+            }
+            sampled
+        }
+    }
+}
+
+fn robots_sitemaps(robots: &str) -> Vec<String> {
+    ROBOTS_SITEMAP_REGEX
+        .captures_iter(robots)
+        .map(|captures| captures["url"].to_string())
+        .collect()
+}
+
+fn parse_sitemap(xml: &str) -> Sitemap {
+    let mut sitemap = Sitemap::default();
+    for captures in SITEMAP_ENTRY_REGEX.captures_iter(xml) {
+        let loc = unescape(&captures["loc"]);
+        match &captures["kind"] {
+            "sitemap" => sitemap.sitemaps.push(loc),
+            _ => sitemap.pages.push(loc),
+        }
+    }
+    sitemap
+}
+
+/// Text of a sitemap, which is gzipped when it starts with the gzip magic number. A gzipped
+/// sitemap is truncated after `MAX_SITEMAP_SIZE` bytes.
+fn decompress(bytes: Vec<u8>) -> String {
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut xml = Vec::new();
+        if GzDecoder::new(bytes.as_slice())
+            .take(MAX_SITEMAP_SIZE as u64)
+            .read_to_end(&mut xml)
+            .is_ok()
+        {
+            return String::from_utf8_lossy(&xml).into_owned();
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn unescape(loc: &str) -> String {
+    let loc = loc
+        .strip_prefix("<![CDATA[")
+        .and_then(|loc| loc.strip_suffix("]]>"))
+        .unwrap_or(loc);
+    loc.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// First path segment of a URL, empty for the root.
+fn section(url: &str) -> &str {
+    let path = url.find("://").map(|i| &url[i + 3..]).unwrap_or(url);
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    path.split('/').nth(1).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    fn urls(paths: &[&str]) -> Vec<String> {
+        paths
+            .iter()
+            .map(|path| format!("https://host{path}"))
+            .collect()
+    }
+
+    #[test]
+    fn test_robots_sitemaps() {
+        let robots = "User-agent: *\nDisallow: /admin\n\nSitemap: https://host/sitemap_index.xml\nsitemap:https://host/news.xml # news\n";
+        assert_eq!(
+            vec![
+                String::from("https://host/sitemap_index.xml"),
+                String::from("https://host/news.xml"),
+            ],
+            robots_sitemaps(robots)
+        );
+    }
+
+    #[test]
+    fn test_parse_sitemap() {
+        let index = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <sitemap><loc>https://host/pages.xml.gz</loc><lastmod>2024-01-01</lastmod></sitemap>
+              <sitemap>
+                <loc>https://host/posts.xml</loc>
+              </sitemap>
+            </sitemapindex>"#;
+        assert_eq!(
+            Sitemap {
+                sitemaps: urls(&["/pages.xml.gz", "/posts.xml"]),
+                pages: Vec::new(),
+            },
+            parse_sitemap(index)
+        );
+        let urlset = r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <url><loc>https://host/</loc><priority>1.0</priority></url>
+              <url><loc>https://host/search?q=a&amp;page=2</loc></url>
+              <url><loc><![CDATA[https://host/about]]></loc></url>
+            </urlset>"#;
+        assert_eq!(
+            urls(&["/", "/search?q=a&page=2", "/about"]),
+            parse_sitemap(urlset).pages
+        );
+    }
+
+    #[test]
+    fn test_decompress() {
+        let xml = "<urlset><url><loc>https://host/</loc></url></urlset>";
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(xml.as_bytes()).unwrap();
+        assert_eq!(xml, decompress(encoder.finish().unwrap()));
+        assert_eq!(xml, decompress(xml.as_bytes().to_vec()));
+    }
+
+    #[test]
+    fn test_sample() {
+        let pages = urls(&[
+            "/", "/blog/1", "/blog/2", "/blog/3", "/blog/4", "/docs/a", "/docs/b", "/pricing",
+        ]);
+        assert_eq!(
+            urls(&["/", "/blog/1", "/blog/2"]),
+            sample(pages.clone(), 3, SitemapSampling::First)
+        );
+        assert_eq!(
+            urls(&["/", "/blog/2", "/blog/4", "/docs/b"]),
+            sample(pages.clone(), 4, SitemapSampling::Spread)
+        );
+        assert_eq!(
+            urls(&["/", "/blog/1", "/docs/a", "/pricing", "/blog/2"]),
+            sample(pages.clone(), 5, SitemapSampling::BySection)
+        );
+        assert_eq!(pages, sample(pages.clone(), 8, SitemapSampling::BySection));
+        for sampling in [
+            SitemapSampling::First,
+            SitemapSampling::Spread,
+            SitemapSampling::BySection,
+        ] {
+            assert!(sample(pages.clone(), 0, sampling).is_empty());
+        }
+    }
+}
@@ -4,9 +4,11 @@ use css_color::Srgb;
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::Color;
+use crate::css::rule::{parse_declarations, parse_rules};
+use crate::{Color, ColorDeclaration, ColorPair, PropertyCategory, StyleOrigin};
 
 mod keyword;
+mod rule;
 
 lazy_static! {
     static ref COLOR_KEYWORDS: HashMap<&'static str, &'static str> = keyword::colors();
@@ -20,6 +22,25 @@ lazy_static! {
         .as_str()
     )
     .unwrap();
+    static ref VALUE_KEYWORD_REGEX: Regex = Regex::new(
+        format!(r"(?:^|[\s,(])(?P<keyword>{})(?:$|[\s,)])", COLOR_KEYWORDS
+            .keys()
+            .map(|s| &**s)
+            .collect::<Vec<_>>()
+            .join("|")
+            .as_str())
+        .as_str()
+    )
+    .unwrap();
+    static ref URL_REGEX: Regex =
+        Regex::new(r#"url\(\s*(?:"[^"]*"|'[^']*'|[^)]*)\s*\)"#).unwrap();
+    static ref DATA_URI_REGEX: Regex = Regex::new(
+        r#"url\(\s*(?:"(?P<double>data:[^"]*)"|'(?P<single>data:[^']*)'|(?P<bare>data:[^)\s]*))\s*\)"#
+    )
+    .unwrap();
+    static ref SVG_URL_REGEX: Regex =
+        Regex::new(r#"url\(\s*['"]?(?P<url>[^'")\s]+\.svg(?:[?#][^'")\s]*)?)['"]?\s*\)"#)
+            .unwrap();
     static ref SRGB_REGEX: Regex = Regex::new(
         // /-- two patterns handling sRGB color space fns and hexidecimal notation
         // | /-- fn names hsl, hsla, hwb, rgb and rgba
@@ -33,32 +54,209 @@ lazy_static! {
     .unwrap();
 }
 
+/// Rounds each channel to the nearest byte, the one conversion shared by scraped and
+/// parsed colors.
 impl From<Srgb> for Color {
     fn from(v: Srgb) -> Self {
+        let channel = |v: f32| (v.clamp(0., 1.) * 255.).round() as u8;
         Color::rgba(
-            (v.red * 255.) as u8,
-            (v.green * 255.) as u8,
-            (v.blue * 255.) as u8,
-            (v.alpha * 255.) as u8,
+            channel(v.red),
+            channel(v.green),
+            channel(v.blue),
+            channel(v.alpha),
         )
     }
 }
 
-pub(crate) fn extract_colors(css: &str) -> HashSet<Color> {
-    let mut colors = HashSet::new();
+/// Distinct colors of a stylesheet, ordered so results are stable between runs.
+pub(crate) fn extract_colors(css: &str) -> Vec<Color> {
+    // URLs can't hold colors, but data URIs often contain text that looks like one
+    let css = URL_REGEX.replace_all(css, "url()");
+    let css = css.as_ref();
+    let mut seen = HashSet::new();
+    let mut colors = Vec::new();
     for srgb_match in SRGB_REGEX.find_iter(css) {
         if let Ok(srgb) = srgb_match.as_str().parse::<Srgb>() {
-            colors.insert(Color::from(srgb));
+            colors.push(Color::from(srgb));
         }
     }
     for keyword_captures in KEYWORD_REGEX.captures_iter(css) {
-        colors.insert(parse_keyword_str(
+        colors.push(parse_keyword_str(
             keyword_captures.name("keyword").unwrap().as_str(),
         ));
     }
+    colors.retain(|color| seen.insert(*color));
     colors
 }
 
+/// Foreground and background colors of rules declaring both `color` and a background color.
+pub(crate) fn extract_color_pairs(css: &str) -> Vec<ColorPair> {
+    let mut pairs = Vec::new();
+    for rule in parse_rules(css) {
+        let foreground = match rule.declaration("color") {
+            Some(declaration) => parse_color_value(&declaration.value).into_iter().next(),
+            None => None,
+        };
+        let background = rule
+            .declaration("background-color")
+            .or_else(|| rule.declaration("background"))
+            .and_then(|declaration| parse_color_value(&declaration.value).into_iter().last());
+        if let (Some(foreground), Some(background)) = (foreground, background) {
+            pairs.push(ColorPair {
+                selector: rule.selector,
+                foreground,
+                background,
+            });
+        }
+    }
+    pairs
+}
+
+/// Every color of every declaration, in source order.
+pub(crate) fn extract_color_declarations(css: &str) -> Vec<ColorDeclaration> {
+    let mut declarations = Vec::new();
+    for rule in parse_rules(css) {
+        for declaration in &rule.declarations {
+            for color in parse_color_value(&declaration.value) {
+                declarations.push(ColorDeclaration {
+                    selector: rule.selector.clone(),
+                    property: declaration.property.clone(),
+                    category: PropertyCategory::of(&declaration.property),
+                    color,
+                    origin: StyleOrigin::default(),
+                });
+            }
+        }
+    }
+    declarations
+}
+
+/// Background color of the last rule styling `body`, or of the root element when no rule
+/// styles the body.
+pub(crate) fn extract_page_background(css: &str) -> Option<Color> {
+    let mut body = None;
+    let mut root = None;
+    for rule in parse_rules(css) {
+        let background = rule
+            .declaration("background-color")
+            .or_else(|| rule.declaration("background"))
+            .and_then(|declaration| parse_color_value(&declaration.value).into_iter().last());
+        let Some(background) = background else {
+            continue;
+        };
+        for selector in rule.selector.split(',').map(str::trim) {
+            match selector {
+                "body" => body = Some(background),
+                "html" | ":root" => root = Some(background),
+                _ => {}
+            }
+        }
+    }
+    body.or(root)
+}
+
+/// Colors of the declarations of a rule block or `style` attribute, in source order.
+pub(crate) fn extract_declaration_colors(block: &str) -> Vec<Color> {
+    parse_declarations(block)
+        .iter()
+        .flat_map(|declaration| parse_color_value(&declaration.value))
+        .collect()
+}
+
+/// URLs of SVG images referenced with `url()`, excluding data URIs.
+pub(crate) fn extract_svg_urls(css: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for captures in SVG_URL_REGEX.captures_iter(css) {
+        let url = &captures["url"];
+        if !url.starts_with("data:") && !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+/// Properties and colors of a CSS fragment such as the body of a styled component, which
+/// mixes top level declarations with nested rules.
+pub(crate) fn extract_fragment_colors(css: &str) -> Vec<(String, Color)> {
+    let mut top_level = String::new();
+    let mut depth = 0usize;
+    for c in css.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            // ends the declaration before a nested rule's selector
+            _ if depth == 0 => top_level.push(c),
+            _ => {}
+        }
+        if c == '}' && depth == 0 {
+            top_level.push(';');
+        }
+    }
+    let nested = parse_rules(css)
+        .into_iter()
+        .flat_map(|rule| rule.declarations);
+    parse_declarations(&top_level)
+        .into_iter()
+        .chain(nested)
+        .flat_map(|declaration| {
+            parse_color_value(&declaration.value)
+                .into_iter()
+                .map(move |color| (declaration.property.clone(), color))
+        })
+        .collect()
+}
+
+/// `data:` URIs referenced with `url()`, in source order.
+pub(crate) fn extract_data_uris(css: &str) -> Vec<String> {
+    DATA_URI_REGEX
+        .captures_iter(css)
+        .filter_map(|captures| {
+            captures
+                .name("double")
+                .or_else(|| captures.name("single"))
+                .or_else(|| captures.name("bare"))
+                .map(|uri| uri.as_str().to_string())
+        })
+        .collect()
+}
+
+/// Colors of a declaration value in the order they appear.
+pub(crate) fn parse_color_value(value: &str) -> Vec<Color> {
+    let value = URL_REGEX.replace_all(value, "");
+    let mut colors = Vec::new();
+    for srgb_match in SRGB_REGEX.find_iter(&value) {
+        if let Ok(srgb) = srgb_match.as_str().parse::<Srgb>() {
+            colors.push((srgb_match.start(), Color::from(srgb)));
+        }
+    }
+    for keyword_captures in VALUE_KEYWORD_REGEX.captures_iter(&value) {
+        let keyword = keyword_captures.name("keyword").unwrap();
+        colors.push((keyword.start(), parse_keyword_str(keyword.as_str())));
+    }
+    colors.sort_by_key(|(position, _)| *position);
+    colors.into_iter().map(|(_, color)| color).collect()
+}
+
+/// Color keywords and their colors, sorted by name.
+pub(crate) fn keyword_colors() -> Vec<(&'static str, Color)> {
+    let mut colors: Vec<(&'static str, Color)> = COLOR_KEYWORDS
+        .iter()
+        .map(|(keyword, hex_rgb)| (*keyword, parse_hex_rgb_str(hex_rgb)))
+        .collect();
+    colors.sort_by_key(|(keyword, _)| *keyword);
+    colors
+}
+
+/// Parses 3, 4, 6 or 8 digit hex notation with or without a leading `#`.
+pub(crate) fn parse_hex_color(hex: &str) -> Option<Color> {
+    let hex_rgb = hex.strip_prefix('#').unwrap_or(hex);
+    if matches!(hex_rgb.len(), 3 | 4 | 6 | 8) && hex_rgb.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(parse_hex_rgb_str(hex_rgb))
+    } else {
+        None
+    }
+}
+
 fn parse_hex_rgb_str(hex_rgb: &str) -> Color {
     match hex_rgb.len() {
         3 => Color::rgb(
@@ -129,30 +327,155 @@ mod tests {
         test_css("rgb(4    5   6)", Color::rgb(4, 5, 6));
         test_css("rgba(7 8 9 / 1)", Color::rgb(7, 8, 9));
         test_css("rgba(10 11 12 / 0)", Color::rgba(10, 11, 12, 0));
-        test_css("rgb(9 8 7 / .5)", Color::rgba(9, 8, 7, 127));
-        test_css("rgb(6 5 4 / 50%)", Color::rgba(6, 5, 4, 127));
-        test_css("rgb(1% 11% 100% / .5)", Color::rgba(2, 28, 255, 127));
-        test_css("rgb(2% 22% 100% / 50%)", Color::rgba(5, 56, 255, 127));
+        test_css("rgb(9 8 7 / .5)", Color::rgba(9, 8, 7, 128));
+        test_css("rgb(6 5 4 / 50%)", Color::rgba(6, 5, 4, 128));
+        test_css("rgb(1% 11% 100% / .5)", Color::rgba(3, 28, 255, 128));
+        test_css("rgb(2% 22% 100% / 50%)", Color::rgba(5, 56, 255, 128));
     }
 
     #[test]
     fn test_extracts_hsl_colors() {
         test_css("hsl(360 100% 50%)", Color::rgb(255, 0, 0));
-        test_css("hsl(150 70% 20% / .5)", Color::rgba(15, 86, 51, 127));
-        test_css("hsl(360, 100%, 50%, .5)", Color::rgba(255, 0, 0, 127));
-        test_css("hsla(360 100% 50% / .5)", Color::rgba(255, 0, 0, 127));
-        test_css("hsla(360, 100%, 50%, .5)", Color::rgba(255, 0, 0, 127));
-        test_css("hsla(360, 100%, 50%, .5)", Color::rgba(255, 0, 0, 127));
+        test_css("hsl(150 70% 20% / .5)", Color::rgba(15, 87, 51, 128));
+        test_css("hsl(360, 100%, 50%, .5)", Color::rgba(255, 0, 0, 128));
+        test_css("hsla(360 100% 50% / .5)", Color::rgba(255, 0, 0, 128));
+        test_css("hsla(360, 100%, 50%, .5)", Color::rgba(255, 0, 0, 128));
+        test_css("hsla(360, 100%, 50%, .5)", Color::rgba(255, 0, 0, 128));
     }
 
     #[test]
     fn test_extracts_hwb_colors() {
-        test_css("hwb(194 0% 0%)", Color::rgb(0, 195, 255));
-        test_css("hwb(194 0% 0% / .5)", Color::rgba(0, 195, 255, 127));
+        test_css("hwb(194 0% 0%)", Color::rgb(0, 196, 255));
+        test_css("hwb(194 0% 0% / .5)", Color::rgba(0, 196, 255, 128));
     }
 
     #[test]
     fn test_extracts_keyword_colors() {
         test_css(":green;", Color::rgb(0, 128, 0));
     }
+
+    #[test]
+    fn test_parse_color_value() {
+        assert_eq!(
+            vec![Color::rgb(0, 0, 0), Color::rgb(255, 0, 0)],
+            parse_color_value("1px solid #000, 0 0 2px red")
+        );
+        assert!(parse_color_value("url(red.png) no-repeat var(--red)").is_empty());
+    }
+
+    #[test]
+    fn test_extracts_color_pairs() {
+        let pairs = extract_color_pairs(
+            "body { color: #111; background: url(bg.png) #fafafa; } \
+             .btn { background-color: blue; color: white; } \
+             p { color: #222 }",
+        );
+        assert_eq!(2, pairs.len());
+        assert_eq!("body", pairs[0].selector);
+        assert_eq!(Color::rgb(0x11, 0x11, 0x11), pairs[0].foreground);
+        assert_eq!(Color::rgb(0xfa, 0xfa, 0xfa), pairs[0].background);
+        assert_eq!(Color::rgb(255, 255, 255), pairs[1].foreground);
+        assert_eq!(Color::rgb(0, 0, 255), pairs[1].background);
+    }
+
+    #[test]
+    fn test_extracts_color_declarations() {
+        let declarations = extract_color_declarations(
+            ":root { --brand: #e4002b; } \
+             a:hover { color: red; border: 1px solid #000; text-decoration: underline }",
+        );
+        let summary: Vec<(&str, &str, Color)> = declarations
+            .iter()
+            .map(|d| (d.selector.as_str(), d.property.as_str(), d.color))
+            .collect();
+        assert_eq!(
+            vec![
+                (":root", "--brand", Color::rgb(0xe4, 0x00, 0x2b)),
+                ("a:hover", "color", Color::rgb(255, 0, 0)),
+                ("a:hover", "border", Color::rgb(0, 0, 0)),
+            ],
+            summary
+        );
+    }
+
+    #[test]
+    fn test_extracts_svg_urls() {
+        assert_eq!(
+            vec![
+                String::from("img/pattern.svg"),
+                String::from("/icons.svg#arrow"),
+            ],
+            extract_svg_urls(
+                ".a { background: url(img/pattern.svg) } \
+                 .b { mask: url( '/icons.svg#arrow' ) } \
+                 .c { background-image: url(\"img/pattern.svg\"), url(photo.png) } \
+                 .d { background: url(data:image/svg+xml,%3Csvg%3E.svg) }"
+            )
+        );
+    }
+
+    #[test]
+    fn test_ignores_colors_in_urls() {
+        let css = r#".icon { background: url("data:image/svg+xml,<svg fill='#abc'><path fill='rgb(1,2,3)'/></svg>") #fff; }
+                     .logo { background-image: url(img/#f00.png); color: red; }"#;
+        assert_eq!(
+            vec![Color::rgb(255, 255, 255), Color::rgb(255, 0, 0)],
+            extract_colors(css)
+        );
+        let declarations: Vec<Color> = extract_color_declarations(css)
+            .into_iter()
+            .map(|declaration| declaration.color)
+            .collect();
+        assert_eq!(
+            vec![Color::rgb(255, 255, 255), Color::rgb(255, 0, 0)],
+            declarations
+        );
+    }
+
+    #[test]
+    fn test_extracts_fragment_colors() {
+        assert_eq!(
+            vec![
+                (String::from("color"), Color::rgb(0x11, 0x11, 0x11)),
+                (String::from("border"), Color::rgb(0xee, 0xee, 0xee)),
+                (String::from("background"), Color::rgb(0, 0, 255)),
+            ],
+            extract_fragment_colors(
+                "color: #111; padding: 4px; &:hover { background: blue; } border: 1px solid #eee"
+            )
+        );
+    }
+
+    #[test]
+    fn test_extracts_data_uris() {
+        assert_eq!(
+            vec![
+                String::from("data:image/svg+xml,<svg fill='red'/>"),
+                String::from("data:image/png;base64,iVBORw0KGgo="),
+                String::from("data:image/gif;base64,R0lGOD"),
+            ],
+            extract_data_uris(
+                r#".a { background: url("data:image/svg+xml,<svg fill='red'/>") }
+                   .b { background: url( data:image/png;base64,iVBORw0KGgo= ) }
+                   .c { background: url('data:image/gif;base64,R0lGOD'), url(photo.png) }"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_extracts_page_background() {
+        assert_eq!(
+            Some(Color::rgb(0xfa, 0xfa, 0xfa)),
+            extract_page_background(
+                "html { background: #000 } \
+                 html, body { background: url(bg.png) #fafafa; } \
+                 body .card { background: red }"
+            )
+        );
+        assert_eq!(
+            Some(Color::rgb(0x11, 0x11, 0x11)),
+            extract_page_background(":root { background-color: #111 } body { margin: 0 }")
+        );
+        assert_eq!(None, extract_page_background("main { background: #111 }"));
+    }
 }
@@ -0,0 +1,172 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref COMMENT_REGEX: Regex = Regex::new(r"/\*[\s\S]*?\*/").unwrap();
+}
+
+pub(crate) struct Rule {
+    pub(crate) selector: String,
+    pub(crate) declarations: Vec<Declaration>,
+}
+
+pub(crate) struct Declaration {
+    pub(crate) property: String,
+    pub(crate) value: String,
+}
+
+impl Rule {
+    pub(crate) fn declaration(&self, property: &str) -> Option<&Declaration> {
+        self.declarations
+            .iter()
+            .rev()
+            .find(|declaration| declaration.property == property)
+    }
+}
+
+/// Style rules of a stylesheet, including those nested in conditional group at-rules such
+/// as `@media` and `@supports`.
+pub(crate) fn parse_rules(css: &str) -> Vec<Rule> {
+    let css = COMMENT_REGEX.replace_all(css, "");
+    let mut rules = Vec::new();
+    collect_rules(&css, &mut rules);
+    rules
+}
+
+fn collect_rules(css: &str, rules: &mut Vec<Rule>) {
+    let mut rest = css;
+    while let Some(open) = find_unquoted(rest, |c| c == '{' || c == ';') {
+        let prelude = rest[..open].trim();
+        if rest[open..].starts_with(';') {
+            rest = &rest[open + 1..];
+            continue;
+        }
+        let close = matching_brace(rest, open);
+        let block = &rest[open + 1..close];
+        if let Some(at_rule) = prelude.strip_prefix('@') {
+            let name = at_rule
+                .split(|c: char| !c.is_alphanumeric() && c != '-')
+                .next();
+            if matches!(
+                name,
+                Some("media" | "supports" | "layer" | "container" | "document" | "scope")
+            ) {
+                collect_rules(block, rules);
+            }
+        } else if !prelude.is_empty() {
+            rules.push(Rule {
+                selector: prelude.to_string(),
+                declarations: parse_declarations(block),
+            });
+        }
+        rest = &rest[(close + 1).min(rest.len())..];
+    }
+}
+
+pub(crate) fn parse_declarations(block: &str) -> Vec<Declaration> {
+    let mut declarations = Vec::new();
+    let mut rest = block;
+    loop {
+        let end = find_unquoted(rest, |c| c == ';').unwrap_or(rest.len());
+        if let Some((property, value)) = rest[..end].split_once(':') {
+            let property = property.trim().to_ascii_lowercase();
+            let value = value.trim();
+            let value = value.strip_suffix("!important").unwrap_or(value).trim();
+            if !property.is_empty() && !property.contains('{') {
+                declarations.push(Declaration {
+                    property,
+                    value: value.to_string(),
+                });
+            }
+        }
+        if end >= rest.len() {
+            break;
+        }
+        rest = &rest[end + 1..];
+    }
+    declarations
+}
+
+/// Byte index of the first char matching `predicate` outside of quotes and parentheses.
+fn find_unquoted(s: &str, predicate: impl Fn(char) -> bool) -> Option<usize> {
+    let mut quote = None;
+    let mut depth = 0usize;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (_, '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, c) if depth == 0 && predicate(c) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Index of the brace closing the one at `open`, or the end of `s` when unbalanced.
+fn matching_brace(s: &str, open: usize) -> usize {
+    let mut depth = 0usize;
+    let mut rest = &s[open..];
+    let mut offset = open;
+    while let Some(i) = find_unquoted(rest, |c| c == '{' || c == '}') {
+        if rest[i..].starts_with('{') {
+            depth += 1;
+        } else {
+            depth -= 1;
+            if depth == 0 {
+                return offset + i;
+            }
+        }
+        offset += i + 1;
+        rest = &rest[i + 1..];
+    }
+    s.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_rules_and_declarations() {
+        let rules = parse_rules(
+            r#"
+            /* a { color: red; } */
+            @import url("x.css");
+            @charset "utf-8";
+            body, html { color: #333; background: url("a;b.png") #fff !important }
+            @media (prefers-color-scheme: dark) {
+                body { color: #eee; }
+            }
+            @font-face { font-family: "x"; }
+            a:hover{color:red}
+            "#,
+        );
+        let selectors: Vec<&str> = rules.iter().map(|r| r.selector.as_str()).collect();
+        assert_eq!(vec!["body, html", "body", "a:hover"], selectors);
+        assert_eq!(2, rules[0].declarations.len());
+        assert_eq!("color", rules[0].declarations[0].property);
+        assert_eq!("#333", rules[0].declarations[0].value);
+        assert_eq!(
+            r#"url("a;b.png") #fff"#,
+            rules[0].declaration("background").unwrap().value
+        );
+        assert_eq!("#eee", rules[1].declaration("color").unwrap().value);
+        assert_eq!("red", rules[2].declaration("color").unwrap().value);
+    }
+
+    #[test]
+    fn test_tolerates_unbalanced_css() {
+        let rules = parse_rules("a { color: red; } b { color: blue;");
+        assert_eq!(2, rules.len());
+        assert_eq!("blue", rules[1].declaration("color").unwrap().value);
+    }
+}
@@ -0,0 +1,100 @@
+use base64::alphabet::STANDARD;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Decoded payload of a `data:` URI.
+pub(crate) struct DataUri {
+    /// Lowercased media type without parameters, `text/plain` when omitted.
+    pub(crate) media_type: String,
+    pub(crate) bytes: Vec<u8>,
+}
+
+pub(crate) fn decode_data_uri(uri: &str) -> Option<DataUri> {
+    let rest = uri.trim();
+    if !rest.get(..5)?.eq_ignore_ascii_case("data:") {
+        return None;
+    }
+    let (header, data) = rest[5..].split_once(',')?;
+    let mut parameters = header.split(';').map(str::trim);
+    let media_type = match parameters.next() {
+        Some("") | None => String::from("text/plain"),
+        Some(media_type) => media_type.to_ascii_lowercase(),
+    };
+    let base64 = parameters.any(|parameter| parameter.eq_ignore_ascii_case("base64"));
+    let decoded = percent_decode(data);
+    let bytes = if base64 {
+        let encoded: Vec<u8> = decoded
+            .into_iter()
+            .filter(|b| !b.is_ascii_whitespace())
+            .collect();
+        BASE64.decode(encoded).ok()?
+    } else {
+        decoded
+    };
+    Some(DataUri { media_type, bytes })
+}
+
+fn percent_decode(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes.get(i + 1..i + 3) {
+            Some(hex) if bytes[i] == b'%' => std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decodes_percent_encoded_data() {
+        let uri = decode_data_uri(
+            "data:image/svg+xml;charset=utf8,%3Csvg xmlns='http://www.w3.org/2000/svg'%3E\
+             %3Cpath fill='%23E4002B'/%3E%3C/svg%3E",
+        )
+        .unwrap();
+        assert_eq!("image/svg+xml", uri.media_type);
+        assert_eq!(
+            "<svg xmlns='http://www.w3.org/2000/svg'><path fill='#E4002B'/></svg>",
+            String::from_utf8(uri.bytes).unwrap()
+        );
+        let utf8 = decode_data_uri("data:,100%25 %zz").unwrap();
+        assert_eq!("text/plain", utf8.media_type);
+        assert_eq!(b"100% %zz".to_vec(), utf8.bytes);
+    }
+
+    #[test]
+    fn test_decodes_base64_data() {
+        let uri = decode_data_uri("DATA:image/png;Base64,iVBORw0K\nGgo=").unwrap();
+        assert_eq!("image/png", uri.media_type);
+        assert_eq!(b"\x89PNG\r\n\x1a\n".to_vec(), uri.bytes);
+        let unpadded = decode_data_uri("data:text/plain;base64,aGk").unwrap();
+        assert_eq!(b"hi".to_vec(), unpadded.bytes);
+        assert!(decode_data_uri("data:image/png;base64,!!!").is_none());
+        assert!(decode_data_uri("https://host/logo.png").is_none());
+        assert!(decode_data_uri("data:image/png").is_none());
+    }
+}
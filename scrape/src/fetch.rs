@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use encoding_rs::Encoding;
+use reqwest::header::{CONTENT_TYPE, IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqwest::{Client, StatusCode};
+use tokio::sync::{OnceCell, Semaphore};
+use tokio::time::Instant;
+
+use crate::fetch::cache::{CachedResponse, HttpCache};
+use crate::fetch::decode::{decode_css, decode_html, decode_text};
+use crate::fetch::robots::Robots;
+use crate::ColorScrapeError;
+
+pub use self::cache::CacheOptions;
+
+mod cache;
+mod decode;
+mod robots;
+
+/// How politely documents are requested.
+#[derive(Clone, Debug)]
+pub struct FetchOptions {
+    /// Sent with every request and matched against the groups of `robots.txt`.
+    pub user_agent: String,
+    /// Skips what `robots.txt` disallows and waits its `Crawl-delay` between requests.
+    /// Only turn this off for sites you own.
+    pub respect_robots_txt: bool,
+    /// Least time between the starts of requests to the same host.
+    pub min_delay: Duration,
+    /// Most requests in flight to the same host at once.
+    pub max_concurrent_per_host: usize,
+    /// Caches responses on disk across scrapes.
+    pub cache: Option<CacheOptions>,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            user_agent: format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            respect_robots_txt: true,
+            min_delay: Duration::from_millis(100),
+            max_concurrent_per_host: 2,
+            cache: None,
+        }
+    }
+}
+
+/// Fetches documents, keeping successful responses so that resources shared by the pages
+/// of a crawl are only requested once, also when the pages are scraped at the same time.
+/// Requests to a host are spaced and capped according
+/// to the [`FetchOptions`] and the host's `robots.txt`, and served from the HTTP cache
+/// when it has a fresh response.
+pub(crate) struct Fetcher {
+    client: Client,
+    options: FetchOptions,
+    cache: Option<HttpCache>,
+    /// Filled by the first request for a URL, which later ones wait for.
+    documents: Mutex<HashMap<String, Arc<OnceCell<Document>>>>,
+    hosts: Mutex<HashMap<String, Arc<Host>>>,
+}
+
+#[derive(Clone)]
+struct Document {
+    status: StatusCode,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+struct Host {
+    requests: Semaphore,
+    /// Loaded with the first request to the host.
+    robots: OnceCell<Robots>,
+    /// Earliest start of the next request.
+    next_request: Mutex<Instant>,
+}
+
+impl Fetcher {
+    pub(crate) fn new(options: &FetchOptions) -> Result<Self, ColorScrapeError> {
+        Ok(Fetcher {
+            client: Client::builder().user_agent(&options.user_agent).build()?,
+            options: options.clone(),
+            cache: options.cache.as_ref().map(HttpCache::new),
+            documents: Mutex::new(HashMap::new()),
+            hosts: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// A script, manifest or other text, decoded from its BOM or `Content-Type` charset, or
+    /// as UTF-8.
+    pub(crate) async fn text(&self, url: &String) -> Result<String, ColorScrapeError> {
+        let document = self.get(url, None).await?;
+        Ok(decode_text(
+            &document.body,
+            document.content_type.as_deref(),
+        ))
+    }
+
+    /// An HTML page with the encoding it was decoded with.
+    pub(crate) async fn html(
+        &self,
+        url: &String,
+    ) -> Result<(String, &'static Encoding), ColorScrapeError> {
+        let document = self.get(url, None).await?;
+        Ok(decode_html(
+            &document.body,
+            document.content_type.as_deref(),
+        ))
+    }
+
+    /// A stylesheet, decoded with `fallback`, the encoding of the page linking it, when it
+    /// declares none.
+    pub(crate) async fn css(
+        &self,
+        url: &String,
+        fallback: &'static Encoding,
+    ) -> Result<String, ColorScrapeError> {
+        let document = self.get(url, None).await?;
+        Ok(decode_css(
+            &document.body,
+            document.content_type.as_deref(),
+            fallback,
+        ))
+    }
+
+    /// A body of at most `max_size` bytes, such as an image that is decoded in full.
+    pub(crate) async fn bytes(
+        &self,
+        url: &String,
+        max_size: usize,
+    ) -> Result<Vec<u8>, ColorScrapeError> {
+        Ok(self.get(url, Some(max_size)).await?.body)
+    }
+
+    async fn get(
+        &self,
+        url: &String,
+        max_size: Option<usize>,
+    ) -> Result<Document, ColorScrapeError> {
+        let cell = self.document(url);
+        // a failed request leaves the cell empty for the next caller to retry, while an
+        // error response is kept like any other
+        let document = cell.get_or_try_init(|| self.fetch(url, max_size)).await?;
+        if !document.status.is_success() {
+            return Err(ColorScrapeError::BadDocumentResponse());
+        }
+        check_size(url, document.clone(), max_size)
+    }
+
+    async fn fetch(
+        &self,
+        url: &String,
+        max_size: Option<usize>,
+    ) -> Result<Document, ColorScrapeError> {
+        let origin = origin(url);
+        let host = self.host(origin);
+        let mut delay = self.options.min_delay;
+        // robots.txt itself is always allowed, and loading the rules fills its cell
+        if self.options.respect_robots_txt && *url != robots_url(origin) {
+            // an unreachable file leaves the rules unloaded for the next request to retry
+            let robots = host
+                .robots
+                .get_or_try_init(|| self.fetch_robots(&host, origin))
+                .await?;
+            if !robots.allows(&path(url)) {
+                return Err(ColorScrapeError::DisallowedByRobots(url.clone()));
+            }
+            delay = delay.max(robots.crawl_delay.unwrap_or_default());
+        }
+        check_size(
+            url,
+            self.request(&host, url, delay, max_size).await?,
+            max_size,
+        )
+    }
+
+    fn document(&self, url: &str) -> Arc<OnceCell<Document>> {
+        let mut documents = self.documents.lock().unwrap();
+        documents.entry(url.to_string()).or_default().clone()
+    }
+
+    /// Rules for this fetcher's user agent. Following RFC 9309, a missing file allows
+    /// everything and a server error disallows everything, while an unreachable file fails
+    /// the request.
+    async fn fetch_robots(&self, host: &Host, origin: &str) -> Result<Robots, ColorScrapeError> {
+        let url = robots_url(origin);
+        // shares the response with a request for the file itself, such as for its sitemaps
+        let cell = self.document(&url);
+        let document = cell
+            .get_or_try_init(|| self.request(host, &url, self.options.min_delay, None))
+            .await;
+        match document {
+            Ok(document) if document.status.is_success() => Ok(Robots::parse(
+                &decode_text(&document.body, document.content_type.as_deref()),
+                &self.options.user_agent,
+            )),
+            Ok(document) if document.status.is_client_error() => Ok(Robots::default()),
+            Ok(_) => Ok(Robots::disallow_all()),
+            // an offline scrape sends no requests, so only what was cached matters
+            Err(ColorScrapeError::NotCached(_)) => Ok(Robots::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Serves a fresh cached response, or sends a request once the host has a free slot
+    /// and `delay` has passed since the previous request to it started. A stale cached
+    /// response is revalidated. Bodies longer than `max_size` aren't read past the limit.
+    async fn request(
+        &self,
+        host: &Host,
+        url: &String,
+        delay: Duration,
+        max_size: Option<usize>,
+    ) -> Result<Document, ColorScrapeError> {
+        let mut cached = self.cache.as_ref().and_then(|cache| cache.get(url));
+        if let Some(cache) = &self.cache {
+            match cached {
+                Some(cached) if cached.is_fresh() || cache.offline() => {
+                    return Ok(Document::from(cached))
+                }
+                None if cache.offline() => return Err(ColorScrapeError::NotCached(url.clone())),
+                _ => {}
+            }
+        }
+
+        let _permit = host.requests.acquire().await.unwrap();
+        let start = {
+            let mut next_request = host.next_request.lock().unwrap();
+            let start = (*next_request).max(Instant::now());
+            *next_request = start + delay;
+            start
+        };
+        tokio::time::sleep_until(start).await;
+        let mut request = self.client.get(url);
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let mut response = request.send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        if let (StatusCode::NOT_MODIFIED, Some(mut cached), Some(cache)) =
+            (status, cached.take(), &self.cache)
+        {
+            if cached.revalidated(&headers) {
+                cache.put(url, &cached);
+            }
+            return Ok(Document::from(cached));
+        }
+        let too_large = |size: usize| max_size.is_some_and(|max_size| size > max_size);
+        if response
+            .content_length()
+            .is_some_and(|length| too_large(length as usize))
+        {
+            return Err(ColorScrapeError::DocumentTooLarge(url.clone()));
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            if too_large(body.len()) {
+                return Err(ColorScrapeError::DocumentTooLarge(url.clone()));
+            }
+        }
+        let document = Document {
+            status,
+            content_type: headers
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+            body,
+        };
+        if let (StatusCode::OK, Some(cache)) = (status, &self.cache) {
+            if let Some(cached) = CachedResponse::from_response(&headers, document.body.clone()) {
+                cache.put(url, &cached);
+            }
+        }
+        Ok(document)
+    }
+
+    fn host(&self, origin: &str) -> Arc<Host> {
+        let mut hosts = self.hosts.lock().unwrap();
+        hosts
+            .entry(origin.to_ascii_lowercase())
+            .or_insert_with(|| {
+                Arc::new(Host {
+                    requests: Semaphore::new(self.options.max_concurrent_per_host.max(1)),
+                    robots: OnceCell::new(),
+                    next_request: Mutex::new(Instant::now()),
+                })
+            })
+            .clone()
+    }
+}
+
+impl From<CachedResponse> for Document {
+    fn from(cached: CachedResponse) -> Self {
+        Document {
+            status: StatusCode::OK,
+            content_type: cached.content_type,
+            body: cached.body,
+        }
+    }
+}
+
+/// Fails for a body longer than `max_size`, which may have been fetched without a limit.
+fn check_size(
+    url: &str,
+    document: Document,
+    max_size: Option<usize>,
+) -> Result<Document, ColorScrapeError> {
+    match max_size {
+        Some(max_size) if document.body.len() > max_size => {
+            Err(ColorScrapeError::DocumentTooLarge(url.to_string()))
+        }
+        _ => Ok(document),
+    }
+}
+
+/// Scheme, host and port of an absolute URL.
+pub(crate) fn origin(url: &str) -> &str {
+    let host_start = url.find("://").map(|i| i + 3).unwrap_or(0);
+    let host_end = url[host_start..]
+        .find(['/', '?', '#'])
+        .map(|i| i + host_start)
+        .unwrap_or(url.len());
+    &url[..host_end]
+}
+
+fn robots_url(origin: &str) -> String {
+    format!("{origin}/robots.txt")
+}
+
+/// Path and query of an absolute URL, as matched by `robots.txt` rules.
+fn path(url: &str) -> String {
+    let path = &url[origin(url).len()..];
+    let path = path.split('#').next().unwrap_or(path);
+    if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{path}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_and_path() {
+        assert_eq!("https://host:8080", origin("https://host:8080/a/b?c#d"));
+        assert_eq!("https://host", origin("https://host"));
+        assert_eq!("/a/b?c", path("https://host:8080/a/b?c#d"));
+        assert_eq!("/", path("https://host"));
+        assert_eq!("/?q", path("https://host?q"));
+    }
+}
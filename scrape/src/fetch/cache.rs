@@ -0,0 +1,354 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, CACHE_CONTROL, CONTENT_TYPE, ETAG, LAST_MODIFIED};
+
+/// Where and how much to cache on disk.
+#[derive(Clone, Debug)]
+pub struct CacheOptions {
+    pub dir: PathBuf,
+    /// Total size of the cached bodies in bytes, beyond which the least recently stored
+    /// responses are evicted.
+    pub max_size: u64,
+    /// Serves every request from the cache, however stale, and fails for URLs that aren't
+    /// cached.
+    pub offline: bool,
+}
+
+impl CacheOptions {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_size: 256 * 1024 * 1024,
+            offline: false,
+        }
+    }
+}
+
+/// A successful response stored in the cache.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CachedResponse {
+    pub(crate) body: Vec<u8>,
+    pub(crate) content_type: Option<String>,
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+    /// When the response was stored or last revalidated.
+    pub(crate) stored: SystemTime,
+    /// How long the response is fresh, `None` when it must always be revalidated.
+    pub(crate) max_age: Option<Duration>,
+}
+
+impl CachedResponse {
+    /// The response to cache, or `None` when `Cache-Control` forbids storing it.
+    pub(crate) fn from_response(headers: &HeaderMap, body: Vec<u8>) -> Option<Self> {
+        let mut response = CachedResponse {
+            body,
+            content_type: header(headers, CONTENT_TYPE),
+            etag: None,
+            last_modified: None,
+            stored: SystemTime::now(),
+            max_age: None,
+        };
+        if response.revalidated(headers) {
+            Some(response)
+        } else {
+            None
+        }
+    }
+
+    /// Takes the validators and freshness of a `304 Not Modified` or fresh response,
+    /// returning false when `Cache-Control` forbids storing it.
+    pub(crate) fn revalidated(&mut self, headers: &HeaderMap) -> bool {
+        let directives = header(headers, CACHE_CONTROL).unwrap_or_default();
+        let mut max_age = None;
+        for directive in directives.split(',') {
+            let directive = directive.trim().to_ascii_lowercase();
+            match directive.split_once('=') {
+                Some(("max-age", seconds)) => {
+                    max_age = max_age.or(seconds.trim_matches('"').parse().ok());
+                }
+                // a shared cache's lifetime doesn't apply to a private one
+                Some(_) => {}
+                None if directive == "no-store" => return false,
+                None if directive == "no-cache" => max_age = Some(0),
+                None => {}
+            }
+        }
+        self.etag = header(headers, ETAG).or(self.etag.take());
+        self.last_modified = header(headers, LAST_MODIFIED).or(self.last_modified.take());
+        self.stored = SystemTime::now();
+        self.max_age = max_age.filter(|age| *age > 0).map(Duration::from_secs);
+        true
+    }
+
+    pub(crate) fn is_fresh(&self) -> bool {
+        match (self.max_age, self.stored.elapsed()) {
+            (Some(max_age), Ok(age)) => age < max_age,
+            _ => false,
+        }
+    }
+}
+
+/// Responses stored on disk as a body file and a metadata file named after a hash of the
+/// URL.
+pub(crate) struct HttpCache {
+    options: CacheOptions,
+    /// Total size of the cached bodies, counted when the first response is stored and
+    /// recounted by each eviction.
+    size: Mutex<Option<u64>>,
+}
+
+impl HttpCache {
+    pub(crate) fn new(options: &CacheOptions) -> Self {
+        HttpCache {
+            options: options.clone(),
+            size: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn offline(&self) -> bool {
+        self.options.offline
+    }
+
+    pub(crate) fn get(&self, url: &str) -> Option<CachedResponse> {
+        let meta = fs::read_to_string(self.path(url, "meta")).ok()?;
+        let field = |name: &str| {
+            meta.lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+                .map(str::to_string)
+        };
+        if field("url").as_deref() != Some(url) {
+            return None;
+        }
+        let stored = UNIX_EPOCH + Duration::from_secs(field("stored")?.parse().ok()?);
+        Some(CachedResponse {
+            body: fs::read(self.path(url, "body")).ok()?,
+            content_type: field("content-type"),
+            etag: field("etag"),
+            last_modified: field("last-modified"),
+            stored,
+            max_age: field("max-age")
+                .and_then(|seconds| seconds.parse().ok())
+                .map(Duration::from_secs),
+        })
+    }
+
+    /// Stores a response, evicting the least recently stored ones beyond the size cap.
+    /// The files are written under temporary names and renamed, so that readers never see
+    /// them half written. Failing to write only means the response will be fetched again.
+    pub(crate) fn put(&self, url: &str, response: &CachedResponse) {
+        if response.body.len() as u64 > self.options.max_size {
+            return;
+        }
+        let stored = response
+            .stored
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut meta = format!("url: {url}\nstored: {stored}\n");
+        let fields = [
+            ("content-type", response.content_type.clone()),
+            ("etag", response.etag.clone()),
+            ("last-modified", response.last_modified.clone()),
+            (
+                "max-age",
+                response.max_age.map(|age| age.as_secs().to_string()),
+            ),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                meta.push_str(&format!("{name}: {value}\n"));
+            }
+        }
+        let body_path = self.path(url, "body");
+        let replaced = fs::metadata(&body_path).map_or(0, |metadata| metadata.len());
+        let written = fs::create_dir_all(&self.options.dir)
+            .and_then(|_| write_atomically(&body_path, &response.body))
+            .and_then(|_| write_atomically(&self.path(url, "meta"), meta.as_bytes()));
+        if written.is_err() {
+            return;
+        }
+        let mut size = self.size.lock().unwrap();
+        let total = match *size {
+            Some(total) => (total + response.body.len() as u64).saturating_sub(replaced),
+            None => self.body_files().iter().map(|(_, len, _)| len).sum(),
+        };
+        *size = Some(if total > self.options.max_size {
+            self.evict()
+        } else {
+            total
+        });
+    }
+
+    /// Removes the least recently stored responses until the cache fits its size cap,
+    /// returning the size left.
+    fn evict(&self) -> u64 {
+        let mut bodies = self.body_files();
+        let mut size: u64 = bodies.iter().map(|(_, len, _)| len).sum();
+        bodies.sort();
+        for (_, len, path) in bodies {
+            if size <= self.options.max_size {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                let _ = fs::remove_file(path.with_extension("meta"));
+                size -= len;
+            }
+        }
+        size
+    }
+
+    /// Modification time, size and path of each body file.
+    fn body_files(&self) -> Vec<(SystemTime, u64, PathBuf)> {
+        let Ok(entries) = fs::read_dir(&self.options.dir) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "body" {
+                    return None;
+                }
+                let metadata = fs::metadata(&path).ok()?;
+                Some((metadata.modified().ok()?, metadata.len(), path))
+            })
+            .collect()
+    }
+
+    fn path(&self, url: &str, extension: &str) -> PathBuf {
+        self.options
+            .dir
+            .join(format!("{:016x}.{extension}", fnv1a(url.as_bytes())))
+    }
+}
+
+/// Writes to a temporary file next to `path` and renames it, which replaces `path` at once.
+/// The temporary name is unique to the process, as several may share a cache.
+fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(".{}.tmp", std::process::id()));
+    fs::write(&temp, contents).and_then(|_| fs::rename(&temp, path))
+}
+
+/// 64-bit FNV-1a, which unlike the std hasher is stable across Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn header(headers: &HeaderMap, name: reqwest::header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("color_scrape_cache_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_cache_control() {
+        let response = |pairs| CachedResponse::from_response(&headers(pairs), Vec::new());
+        let fresh = response(&[
+            ("cache-control", "public, max-age=3600"),
+            ("etag", "\"v1\""),
+        ])
+        .unwrap();
+        assert_eq!(Some(Duration::from_secs(3600)), fresh.max_age);
+        assert_eq!(Some(String::from("\"v1\"")), fresh.etag);
+        assert!(fresh.is_fresh());
+        let no_cache = response(&[("cache-control", "no-cache, max-age=3600")]).unwrap();
+        assert!(!no_cache.is_fresh());
+        assert!(!response(&[]).unwrap().is_fresh());
+        assert_eq!(None, response(&[("cache-control", "private, no-store")]));
+    }
+
+    #[test]
+    fn test_revalidated_keeps_validators() {
+        let mut response = CachedResponse::from_response(
+            &headers(&[
+                ("etag", "\"v1\""),
+                ("last-modified", "Mon, 01 Jan 2024 00:00:00 GMT"),
+            ]),
+            b"body".to_vec(),
+        )
+        .unwrap();
+        assert!(response.revalidated(&headers(&[("cache-control", "max-age=60")])));
+        assert_eq!(Some(String::from("\"v1\"")), response.etag);
+        assert!(response.last_modified.is_some());
+        assert!(response.is_fresh());
+    }
+
+    #[test]
+    fn test_put_and_get() {
+        let dir = temp_dir("put_and_get");
+        let cache = HttpCache::new(&CacheOptions::new(&dir));
+        let url = "https://host/main.css";
+        assert_eq!(None, cache.get(url));
+        let response = CachedResponse::from_response(
+            &headers(&[
+                ("content-type", "text/css; charset=utf-8"),
+                ("etag", "W/\"abc\""),
+                ("cache-control", "max-age=600"),
+            ]),
+            b"body { color: red }".to_vec(),
+        )
+        .unwrap();
+        cache.put(url, &response);
+        let cached = cache.get(url).unwrap();
+        assert_eq!(response.body, cached.body);
+        assert_eq!(response.content_type, cached.content_type);
+        assert_eq!(response.etag, cached.etag);
+        assert_eq!(response.max_age, cached.max_age);
+        assert!(cached.is_fresh());
+        assert_eq!(None, cache.get("https://host/other.css"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_size_cap() {
+        let dir = temp_dir("size_cap");
+        let cache = HttpCache::new(&CacheOptions {
+            max_size: 10,
+            ..CacheOptions::new(&dir)
+        });
+        let response =
+            |body: &[u8]| CachedResponse::from_response(&HeaderMap::new(), body.to_vec()).unwrap();
+        cache.put("https://host/too-big", &response(b"0123456789a"));
+        assert_eq!(None, cache.get("https://host/too-big"));
+        cache.put("https://host/a", &response(b"012345"));
+        std::thread::sleep(Duration::from_millis(20));
+        cache.put("https://host/b", &response(b"012345"));
+        assert_eq!(None, cache.get("https://host/a"));
+        assert!(cache.get("https://host/b").is_some());
+        // replacing a response counts its new size only
+        cache.put("https://host/b", &response(b"0123"));
+        cache.put("https://host/c", &response(b"012345"));
+        assert!(cache.get("https://host/b").is_some());
+        assert!(cache.get("https://host/c").is_some());
+        // no temporary files are left behind
+        assert_eq!(4, fs::read_dir(&dir).unwrap().count());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
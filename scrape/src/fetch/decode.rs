@@ -0,0 +1,191 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252, X_USER_DEFINED};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// Bytes of an HTML document prescanned for a `<meta>` charset.
+const PRESCAN_LENGTH: usize = 1024;
+
+lazy_static! {
+    static ref META_REGEX: Regex = Regex::new(r"(?i)<meta\s[^>]*>").unwrap();
+    static ref META_CHARSET_REGEX: Regex =
+        Regex::new(r#"(?i)charset\s*=\s*["']?\s*(?P<label>[^"'\s;/>]+)"#).unwrap();
+}
+
+/// Decodes an HTML document following the encoding sniffing algorithm: a BOM, then the
+/// `Content-Type` charset, then a `<meta>` charset near the start. Undeclared documents are
+/// decoded as UTF-8 when valid and as windows-1252 otherwise. Returns the encoding used, which
+/// is the fallback for the document's stylesheets.
+pub(crate) fn decode_html(bytes: &[u8], content_type: Option<&str>) -> (String, &'static Encoding) {
+    let encoding = declared_encoding(bytes, content_type)
+        .or_else(|| meta_charset(bytes))
+        .unwrap_or_else(|| {
+            if std::str::from_utf8(bytes).is_ok() {
+                UTF_8
+            } else {
+                WINDOWS_1252
+            }
+        });
+    (decode(bytes, encoding), encoding)
+}
+
+/// Decodes a stylesheet following CSS Syntax: a BOM, then the `Content-Type` charset, then
+/// an `@charset` rule, then the encoding of the document that linked it.
+pub(crate) fn decode_css(
+    bytes: &[u8],
+    content_type: Option<&str>,
+    fallback: &'static Encoding,
+) -> String {
+    let encoding = declared_encoding(bytes, content_type)
+        .or_else(|| at_charset(bytes))
+        .unwrap_or(fallback);
+    decode(bytes, encoding)
+}
+
+/// Decodes other text, such as scripts and manifests, from a BOM, then the `Content-Type`
+/// charset, then as UTF-8.
+pub(crate) fn decode_text(bytes: &[u8], content_type: Option<&str>) -> String {
+    decode(
+        bytes,
+        declared_encoding(bytes, content_type).unwrap_or(UTF_8),
+    )
+}
+
+/// Encoding of a BOM, or else of the `Content-Type` charset.
+fn declared_encoding(bytes: &[u8], content_type: Option<&str>) -> Option<&'static Encoding> {
+    Encoding::for_bom(bytes)
+        .map(|(encoding, _)| encoding)
+        .or_else(|| content_type.and_then(content_type_charset))
+}
+
+/// Decodes with `encoding`, unless a BOM says otherwise, replacing malformed sequences.
+fn decode(bytes: &[u8], encoding: &'static Encoding) -> String {
+    encoding.decode(bytes).0.into_owned()
+}
+
+fn content_type_charset(content_type: &str) -> Option<&'static Encoding> {
+    content_type.split(';').skip(1).find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("charset") {
+            return None;
+        }
+        Encoding::for_label(value.trim().trim_matches(['"', '\'']).as_bytes())
+    })
+}
+
+/// Encoding of an `@charset "…";` rule, which must be the very first bytes of the sheet.
+fn at_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    let label = bytes.strip_prefix(b"@charset \"")?;
+    let end = label.windows(2).position(|window| window == b"\";")?;
+    let encoding = Encoding::for_label(&label[..end])?;
+    // a sheet that can spell `@charset` in ASCII isn't UTF-16
+    if encoding == UTF_16BE || encoding == UTF_16LE {
+        Some(UTF_8)
+    } else {
+        Some(encoding)
+    }
+}
+
+fn meta_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    let start = String::from_utf8_lossy(&bytes[..bytes.len().min(PRESCAN_LENGTH)]).into_owned();
+    let encoding = META_REGEX.find_iter(&start).find_map(|meta| {
+        let captures = META_CHARSET_REGEX.captures(meta.as_str())?;
+        Encoding::for_label(captures["label"].as_bytes())
+    })?;
+    if encoding == UTF_16BE || encoding == UTF_16LE {
+        Some(UTF_8)
+    } else if encoding == X_USER_DEFINED {
+        Some(WINDOWS_1252)
+    } else {
+        Some(encoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::{ISO_8859_2, SHIFT_JIS};
+
+    use super::*;
+
+    const LATIN1_CSS: &[u8] =
+        b"@charset \"iso-8859-1\";\n.caf\xe9::before { content: \"\xa9\"; color: red }";
+
+    #[test]
+    fn test_decode_css() {
+        let css = decode_css(LATIN1_CSS, Some("text/css"), UTF_8);
+        assert!(css.contains(".café::before"), "{css}");
+        assert!(css.contains("\"©\""));
+        // the header wins over @charset
+        let css = decode_css(LATIN1_CSS, Some("text/css; charset=ISO-8859-2"), UTF_8);
+        assert!(css.contains(".café"));
+        assert_eq!(css, ISO_8859_2.decode(LATIN1_CSS).0);
+        // the BOM wins over everything
+        let css = decode_css(
+            b"\xef\xbb\xbf.caf\xc3\xa9 { color: red }",
+            Some("text/css; charset=windows-1252"),
+            WINDOWS_1252,
+        );
+        assert_eq!(".café { color: red }", css);
+        let utf16: Vec<u8> = [0xff, 0xfe]
+            .into_iter()
+            .chain(".é{}".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        assert_eq!(".é{}", decode_css(&utf16, None, UTF_8));
+        // without declarations, the linking document's encoding is used
+        assert_eq!(".café{}", decode_css(b".caf\xe9{}", None, WINDOWS_1252));
+        assert_eq!(".caf\u{fffd}{}", decode_css(b".caf\xe9{}", None, UTF_8));
+    }
+
+    #[test]
+    fn test_at_charset() {
+        assert_eq!(Some(UTF_8), at_charset(b"@charset \"utf-16le\";"));
+        assert_eq!(Some(SHIFT_JIS), at_charset(b"@charset \"Shift_JIS\"; a{}"));
+        assert_eq!(None, at_charset(b"@charset 'latin1'; a{}"));
+        assert_eq!(None, at_charset(b" @charset \"latin1\"; a{}"));
+        assert_eq!(None, at_charset(b"@charset \"bogus\"; a{}"));
+    }
+
+    #[test]
+    fn test_decode_html() {
+        let html = b"<!doctype html><meta charset=\"windows-1252\"><title>Caf\xe9</title>";
+        let (text, encoding) = decode_html(html, Some("text/html"));
+        assert!(text.contains("<title>Café</title>"), "{text}");
+        assert_eq!(WINDOWS_1252, encoding);
+        let http_equiv =
+            b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=shift_jis\">";
+        assert_eq!(SHIFT_JIS, decode_html(http_equiv, None).1);
+        assert_eq!(
+            ISO_8859_2,
+            decode_html(html, Some("text/html; charset=iso-8859-2")).1
+        );
+        assert_eq!(UTF_8, decode_html(b"<meta charset=utf-16>", None).1);
+        assert_eq!(UTF_8, decode_html("<p>café</p>".as_bytes(), None).1);
+        assert_eq!(WINDOWS_1252, decode_html(b"<p>caf\xe9</p>", None).1);
+    }
+
+    #[test]
+    fn test_decode_text() {
+        assert_eq!(
+            "é",
+            decode_text(b"\xe9", Some("text/javascript; charset=latin1"))
+        );
+        assert_eq!("é", decode_text("é".as_bytes(), Some("application/json")));
+        // @charset only applies to stylesheets
+        let js = b"@charset \"iso-8859-1\";\xc3\xa9";
+        assert_eq!("@charset \"iso-8859-1\";é", decode_text(js, None));
+        assert!(decode_css(js, None, UTF_8).ends_with("Ã©"));
+    }
+
+    #[test]
+    fn test_content_type_charset() {
+        assert_eq!(
+            Some(WINDOWS_1252),
+            content_type_charset("text/css; charset=latin1")
+        );
+        assert_eq!(
+            Some(SHIFT_JIS),
+            content_type_charset("text/html ; Charset = \"Shift_JIS\" ; q=1")
+        );
+        assert_eq!(None, content_type_charset("text/html; format=charset"));
+        assert_eq!(None, content_type_charset("text/html"));
+    }
+}
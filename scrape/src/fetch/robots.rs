@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+/// Longest `Crawl-delay` followed, so that a typo such as `1e20` can't stall a scrape.
+const MAX_CRAWL_DELAY: Duration = Duration::from_secs(60);
+
+/// Rules of a `robots.txt` file for one user agent, following RFC 9309.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Robots {
+    /// Path patterns with whether they are allowed.
+    rules: Vec<(String, bool)>,
+    pub(crate) crawl_delay: Option<Duration>,
+}
+
+struct Group {
+    user_agents: Vec<String>,
+    rules: Vec<(String, bool)>,
+    crawl_delay: Option<Duration>,
+}
+
+impl Robots {
+    /// Rules of the groups naming the product token of `user_agent`, or of the `*` groups
+    /// when none does.
+    pub(crate) fn parse(robots_txt: &str, user_agent: &str) -> Self {
+        let product = user_agent
+            .split('/')
+            .next()
+            .unwrap_or(user_agent)
+            .trim()
+            .to_ascii_lowercase();
+        let mut groups: Vec<Group> = Vec::new();
+        // consecutive user-agent lines share the group that follows them
+        let mut in_rules = true;
+        for line in robots_txt.lines() {
+            let line = line.split('#').next().unwrap_or(line);
+            let Some((field, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match field.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        groups.push(Group {
+                            user_agents: Vec::new(),
+                            rules: Vec::new(),
+                            crawl_delay: None,
+                        });
+                        in_rules = false;
+                    }
+                    groups
+                        .last_mut()
+                        .unwrap()
+                        .user_agents
+                        .push(value.to_ascii_lowercase());
+                }
+                field @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    if let Some(group) = groups.last_mut() {
+                        // an empty disallow allows everything, like no rule at all
+                        if !value.is_empty() {
+                            group.rules.push((value.to_string(), field == "allow"));
+                        }
+                    }
+                }
+                "crawl-delay" => {
+                    in_rules = true;
+                    // negative and NaN delays are invalid, longer ones are clamped
+                    let delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|seconds| *seconds >= 0.)
+                        .and_then(|seconds| {
+                            Duration::try_from_secs_f64(seconds.min(MAX_CRAWL_DELAY.as_secs_f64()))
+                                .ok()
+                        });
+                    if let (Some(group), Some(delay)) = (groups.last_mut(), delay) {
+                        group.crawl_delay = Some(delay);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let names = |group: &Group, agent: &str| group.user_agents.iter().any(|a| a == agent);
+        let agent = if groups.iter().any(|group| names(group, &product)) {
+            product.as_str()
+        } else {
+            "*"
+        };
+        let matching: Vec<&Group> = groups.iter().filter(|group| names(group, agent)).collect();
+        Robots {
+            rules: matching.iter().flat_map(|g| g.rules.clone()).collect(),
+            crawl_delay: matching.iter().find_map(|g| g.crawl_delay),
+        }
+    }
+
+    /// Rules of a site whose `robots.txt` is unreachable because of a server error.
+    pub(crate) fn disallow_all() -> Self {
+        Robots {
+            rules: vec![(String::from("/"), false)],
+            crawl_delay: None,
+        }
+    }
+
+    /// Whether `path`, with its query, may be fetched. The longest matching rule wins, and
+    /// allow wins ties.
+    pub(crate) fn allows(&self, path: &str) -> bool {
+        let mut best: Option<(usize, bool)> = None;
+        for (pattern, allow) in &self.rules {
+            if !matches(pattern, path) {
+                continue;
+            }
+            let better = match best {
+                None => true,
+                Some((length, best_allow)) => {
+                    pattern.len() > length || (pattern.len() == length && *allow && !best_allow)
+                }
+            };
+            if better {
+                best = Some((pattern.len(), *allow));
+            }
+        }
+        best.map(|(_, allow)| allow).unwrap_or(true)
+    }
+}
+
+/// Whether a path pattern with `*` wildcards and an optional `$` end anchor matches `path`.
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let Some(rest) = path.strip_prefix(parts[0]) else {
+        return false;
+    };
+    if parts.len() == 1 {
+        return !anchored || rest.is_empty();
+    }
+    let mut rest = rest;
+    for (i, part) in parts[1..].iter().enumerate() {
+        let last = i == parts.len() - 2;
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS_TXT: &str = "
+        # comments are ignored
+        User-agent: *
+        Disallow: /private
+        Allow: /private/public
+        Disallow: /*.pdf$
+
+        User-agent: Googlebot
+        User-agent: color_scrape
+        Disallow: /search
+        Allow: /search/about
+        Crawl-delay: 2.5
+
+        User-agent: OtherBot
+        Disallow: /
+    ";
+
+    #[test]
+    fn test_default_group() {
+        let robots = Robots::parse(ROBOTS_TXT, "SomeBot/1.0");
+        assert!(!robots.allows("/private/keys"));
+        assert!(robots.allows("/private/public/page"));
+        assert!(!robots.allows("/docs/guide.pdf"));
+        assert!(robots.allows("/docs/guide.pdf?download=1"));
+        assert!(robots.allows("/search"));
+        assert_eq!(None, robots.crawl_delay);
+    }
+
+    #[test]
+    fn test_named_group() {
+        let robots = Robots::parse(ROBOTS_TXT, "Color_Scrape/0.0.1");
+        assert!(robots.allows("/private/keys"));
+        assert!(!robots.allows("/search?q=red"));
+        assert!(robots.allows("/search/about"));
+        assert_eq!(Some(Duration::from_millis(2500)), robots.crawl_delay);
+        assert!(!Robots::parse(ROBOTS_TXT, "OtherBot").allows("/"));
+    }
+
+    #[test]
+    fn test_crawl_delay() {
+        let crawl_delay = |value: &str| {
+            Robots::parse(&format!("User-agent: *\nCrawl-delay: {value}\n"), "bot").crawl_delay
+        };
+        assert_eq!(Some(Duration::from_millis(500)), crawl_delay("0.5"));
+        assert_eq!(Some(MAX_CRAWL_DELAY), crawl_delay("1e20"));
+        assert_eq!(Some(MAX_CRAWL_DELAY), crawl_delay("inf"));
+        assert_eq!(Some(MAX_CRAWL_DELAY), crawl_delay("3600"));
+        assert_eq!(None, crawl_delay("-1"));
+        assert_eq!(None, crawl_delay("NaN"));
+        assert_eq!(None, crawl_delay("soon"));
+    }
+
+    #[test]
+    fn test_permissive_files() {
+        assert!(Robots::parse("", "color_scrape").allows("/"));
+        assert!(Robots::parse("User-agent: *\nDisallow:\n", "color_scrape").allows("/a"));
+        assert!(!Robots::disallow_all().allows("/a"));
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("/fish", "/fish.html"));
+        assert!(!matches("/fish", "/Fish"));
+        assert!(matches("/fish*.php", "/fish/salmon.php?id=1"));
+        assert!(matches("/*.php$", "/a/b.php"));
+        assert!(!matches("/*.php$", "/a/b.php5"));
+        assert!(matches("/$", "/"));
+        assert!(!matches("/$", "/a"));
+        assert!(matches("*", "/anything"));
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crate::color::Color;
+use crate::data_uri::decode_data_uri;
+use crate::graphic::raster::extract_image_colors;
+use crate::graphic::svg::extract_svg_colors;
+use crate::{SiteColors, WeightedColor};
+
+mod raster;
+mod svg;
+
+/// Where a graphic appears on the page.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GraphicKind {
+    /// `<svg>` element in the document.
+    InlineSvg,
+    /// `<img>` whose source, alt text, id or class mentions a logo.
+    Logo,
+    /// Any other `<img>`.
+    Image,
+    /// Favicon, Apple touch icon or Safari mask icon.
+    Icon,
+    /// `og:image` preview image.
+    OpenGraphImage,
+    /// Image referenced by a stylesheet with `url()`.
+    CssImage,
+}
+
+/// Colors of a logo, icon or other image.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Graphic {
+    /// `None` for inline graphics and data URIs.
+    pub url: Option<String>,
+    pub kind: GraphicKind,
+    /// Distinct colors with their share of the graphic, heaviest first.
+    pub colors: Vec<WeightedColor>,
+}
+
+impl Graphic {
+    /// Parses SVG markup or decodes a raster image, or `None` for anything else.
+    pub(crate) fn from_bytes(url: Option<String>, kind: GraphicKind, bytes: &[u8]) -> Option<Self> {
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_ascii_lowercase();
+        if head.trim_start().starts_with('<') && head.contains("<svg") {
+            Some(Self::from_svg(url, kind, &String::from_utf8_lossy(bytes)))
+        } else {
+            let colors = extract_image_colors(bytes)?;
+            Some(Self { url, kind, colors })
+        }
+    }
+
+    /// Weights colors of an SVG document by how often they are used.
+    pub(crate) fn from_svg(url: Option<String>, kind: GraphicKind, svg: &str) -> Self {
+        let colors = extract_svg_colors(svg);
+        let mut counts: Vec<(Color, usize)> = Vec::new();
+        let mut indices: HashMap<Color, usize> = HashMap::new();
+        for color in colors {
+            match indices.get(&color) {
+                Some(i) => counts[*i].1 += 1,
+                None => {
+                    indices.insert(color, counts.len());
+                    counts.push((color, 1));
+                }
+            }
+        }
+        Self::from_counts(url, kind, &counts)
+    }
+
+    /// Decodes an SVG or raster image `data:` URI.
+    pub(crate) fn from_data_uri(kind: GraphicKind, uri: &str) -> Option<Self> {
+        let data = decode_data_uri(uri)?;
+        if !data.media_type.starts_with("image/") {
+            return None;
+        }
+        Self::from_bytes(None, kind, &data.bytes)
+    }
+
+    pub(crate) fn from_counts(
+        url: Option<String>,
+        kind: GraphicKind,
+        counts: &[(Color, usize)],
+    ) -> Self {
+        let total: usize = counts.iter().map(|(_, count)| count).sum();
+        let mut colors: Vec<WeightedColor> = counts
+            .iter()
+            .map(|(color, count)| WeightedColor {
+                color: *color,
+                weight: *count as f64 / total as f64,
+            })
+            .collect();
+        colors.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+        Self { url, kind, colors }
+    }
+}
+
+impl SiteColors {
+    /// Distinct colors of every graphic, in the order first seen.
+    pub fn graphic_colors(&self) -> Vec<Color> {
+        let mut colors: Vec<Color> = Vec::new();
+        for weighted in self.graphics.iter().flat_map(|graphic| &graphic.colors) {
+            if !colors.contains(&weighted.color) {
+                colors.push(weighted.color);
+            }
+        }
+        colors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graphic_from_data_uri() {
+        let svg = Graphic::from_data_uri(
+            GraphicKind::CssImage,
+            "data:image/svg+xml,%3Csvg%3E%3Cpath fill='%23e4002b'/%3E%3C/svg%3E",
+        )
+        .unwrap();
+        assert_eq!(None, svg.url);
+        assert_eq!(Color::rgb(0xe4, 0x00, 0x2b), svg.colors[0].color);
+        // 1x1 navy PNG
+        let png = Graphic::from_data_uri(
+            GraphicKind::Icon,
+            "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAAEElEQVR4AQEF\
+             APr/AAAAgP8CBAGAmMNmJwAAAABJRU5ErkJggg==",
+        )
+        .unwrap();
+        assert_eq!(Color::rgb(0, 0, 0x80), png.colors[0].color);
+        assert_eq!(
+            None,
+            Graphic::from_data_uri(GraphicKind::CssImage, "data:text/css,a{color:red}")
+        );
+    }
+
+    #[test]
+    fn test_graphic_from_svg() {
+        let graphic = Graphic::from_svg(
+            Some(String::from("https://host/logo.svg")),
+            GraphicKind::Logo,
+            r##"<svg><path fill="#e4002b"/><path fill="#e4002b"/><path fill="#fff"/></svg>"##,
+        );
+        assert_eq!(GraphicKind::Logo, graphic.kind);
+        assert_eq!(
+            vec![
+                WeightedColor {
+                    color: Color::rgb(0xe4, 0x00, 0x2b),
+                    weight: 2. / 3.,
+                },
+                WeightedColor {
+                    color: Color::rgb(255, 255, 255),
+                    weight: 1. / 3.,
+                },
+            ],
+            graphic.colors
+        );
+        let from_bytes = Graphic::from_bytes(
+            graphic.url.clone(),
+            GraphicKind::Logo,
+            br##"<?xml version="1.0"?><svg><path fill="#e4002b"/><path fill="#e4002b"/><path fill="#fff"/></svg>"##,
+        );
+        assert_eq!(Some(&graphic), from_bytes.as_ref());
+        assert_eq!(
+            None,
+            Graphic::from_bytes(None, GraphicKind::Icon, b"not found")
+        );
+        let site = SiteColors {
+            graphics: vec![graphic.clone(), graphic],
+            ..Default::default()
+        };
+        assert_eq!(2, site.graphic_colors().len());
+    }
+}
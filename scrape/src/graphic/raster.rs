@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use image::imageops::FilterType;
+use image::io::{Limits, Reader};
+
+use crate::color::Color;
+use crate::quantize::{quantize, QuantizeOptions};
+use crate::WeightedColor;
+
+/// Images are downscaled to fit this size before counting pixels.
+const MAX_DIMENSION: u32 = 64;
+/// Pixels more transparent than this are background and don't count towards coverage.
+const MIN_ALPHA: u8 = 128;
+const DOMINANT_COLORS: usize = 5;
+/// Largest width or height decoded, which keeps a small file from expanding into a huge
+/// bitmap.
+const MAX_DECODED_DIMENSION: u32 = 4096;
+/// Most memory the decoder may allocate.
+const MAX_DECODER_ALLOC: u64 = 64 * 1024 * 1024;
+
+/// Dominant colors of a PNG, JPEG, GIF, WebP or ICO image weighted by the share of
+/// visible pixels they cover, or `None` when the image can't be decoded.
+pub(crate) fn extract_image_colors(bytes: &[u8]) -> Option<Vec<WeightedColor>> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DECODED_DIMENSION);
+    limits.max_image_height = Some(MAX_DECODED_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODER_ALLOC);
+    let mut reader = Reader::new(Cursor::new(bytes)).with_guessed_format().ok()?;
+    reader.limits(limits);
+    let image = reader.decode().ok()?;
+    let image = if image.width() > MAX_DIMENSION || image.height() > MAX_DIMENSION {
+        image.resize(MAX_DIMENSION, MAX_DIMENSION, FilterType::Triangle)
+    } else {
+        image
+    };
+    let mut indices: HashMap<Color, usize> = HashMap::new();
+    let mut counts: Vec<(Color, usize)> = Vec::new();
+    for pixel in image.to_rgba8().pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < MIN_ALPHA {
+            continue;
+        }
+        let color = Color::rgb(r, g, b);
+        match indices.get(&color) {
+            Some(i) => counts[*i].1 += 1,
+            None => {
+                indices.insert(color, counts.len());
+                counts.push((color, 1));
+            }
+        }
+    }
+    let options = QuantizeOptions {
+        colors: DOMINANT_COLORS,
+        ..Default::default()
+    };
+    Some(quantize(&counts, &options))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+
+    use super::*;
+
+    fn encode(image: RgbaImage, format: ImageOutputFormat) -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        let image = match format {
+            ImageOutputFormat::Jpeg(_) => {
+                DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(image).to_rgb8())
+            }
+            _ => DynamicImage::ImageRgba8(image),
+        };
+        image.write_to(&mut bytes, format).unwrap();
+        bytes.into_inner()
+    }
+
+    fn two_color_image() -> RgbaImage {
+        RgbaImage::from_fn(20, 10, |x, _| {
+            if x < 15 {
+                Rgba([0xe4, 0x00, 0x2b, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            }
+        })
+    }
+
+    #[test]
+    fn test_decodes_formats() {
+        for format in [
+            ImageOutputFormat::Png,
+            ImageOutputFormat::Gif,
+            ImageOutputFormat::Ico,
+            ImageOutputFormat::WebP,
+            ImageOutputFormat::Jpeg(100),
+        ] {
+            let colors = extract_image_colors(&encode(two_color_image(), format.clone()))
+                .unwrap_or_else(|| panic!("{format:?}"));
+            let red = colors[0].color;
+            assert!(
+                red.delta_e_2000(&Color::rgb(0xe4, 0x00, 0x2b)) < 3.,
+                "{format:?} {red:?}"
+            );
+            assert!((colors[0].weight - 0.75).abs() < 0.05, "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_ignores_transparent_pixels() {
+        let image = RgbaImage::from_fn(10, 10, |x, _| {
+            if x < 2 {
+                Rgba([0x1a, 0x23, 0x7e, 255])
+            } else {
+                Rgba([255, 0, 0, 0])
+            }
+        });
+        let colors = extract_image_colors(&encode(image, ImageOutputFormat::Png)).unwrap();
+        assert_eq!(
+            vec![WeightedColor {
+                color: Color::rgb(0x1a, 0x23, 0x7e),
+                weight: 1.
+            }],
+            colors
+        );
+    }
+
+    #[test]
+    fn test_reduces_large_images() {
+        let image = RgbaImage::from_fn(400, 300, |x, y| {
+            Rgba([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8, 255])
+        });
+        let colors = extract_image_colors(&encode(image, ImageOutputFormat::Png)).unwrap();
+        assert_eq!(DOMINANT_COLORS, colors.len());
+        let total: f64 = colors.iter().map(|c| c.weight).sum();
+        assert!((total - 1.).abs() < 1e-9);
+        assert_eq!(None, extract_image_colors(b"<html></html>"));
+    }
+
+    #[test]
+    fn test_limits_decoded_size() {
+        let image = RgbaImage::from_pixel(MAX_DECODED_DIMENSION + 1, 1, Rgba([255, 0, 0, 255]));
+        assert_eq!(
+            None,
+            extract_image_colors(&encode(image, ImageOutputFormat::Png))
+        );
+    }
+}
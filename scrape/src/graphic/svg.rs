@@ -0,0 +1,78 @@
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+use crate::color::Color;
+use crate::css::{extract_color_declarations, extract_declaration_colors, parse_color_value};
+
+lazy_static! {
+    static ref ELEMENT_SELECTOR: Selector = Selector::parse("*").unwrap();
+}
+
+const COLOR_ATTRIBUTES: [&str; 6] = [
+    "fill",
+    "stroke",
+    "stop-color",
+    "flood-color",
+    "lighting-color",
+    "color",
+];
+
+/// Every color used by presentation attributes, `style` attributes and `<style>` elements
+/// of an SVG document, in document order and including repeats.
+pub(crate) fn extract_svg_colors(svg: &str) -> Vec<Color> {
+    let fragment = Html::parse_fragment(svg);
+    let mut colors = Vec::new();
+    for element in fragment.select(&ELEMENT_SELECTOR) {
+        let value = element.value();
+        if value.name() == "style" {
+            let css: String = element.text().collect();
+            colors.extend(
+                extract_color_declarations(&css)
+                    .into_iter()
+                    .map(|declaration| declaration.color),
+            );
+            continue;
+        }
+        for attribute in COLOR_ATTRIBUTES {
+            if let Some(attribute_value) = value.attr(attribute) {
+                colors.extend(parse_color_value(attribute_value));
+            }
+        }
+        if let Some(style) = value.attr("style") {
+            colors.extend(extract_declaration_colors(style));
+        }
+    }
+    colors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_svg_colors() {
+        let svg = r##"<?xml version="1.0" encoding="UTF-8"?>
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+              <style>.mark { fill: #e4002b; }</style>
+              <defs>
+                <linearGradient id="g">
+                  <stop offset="0" stop-color="navy"/>
+                  <stop offset="1" style="stop-color: rgb(0 128 0); stop-opacity: .5"/>
+                </linearGradient>
+              </defs>
+              <rect fill="url(#g)" stroke="#000" width="10" height="10"/>
+              <path class="mark" fill="currentColor" d="M0 0h5v5z"/>
+              <circle fill="none" stroke="#000" r="2"/>
+            </svg>"##;
+        assert_eq!(
+            vec![
+                Color::rgb(0xe4, 0x00, 0x2b),
+                Color::rgb(0, 0, 0x80),
+                Color::rgb(0, 128, 0),
+                Color::rgb(0, 0, 0),
+                Color::rgb(0, 0, 0),
+            ],
+            extract_svg_colors(svg)
+        );
+    }
+}
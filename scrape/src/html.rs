@@ -1,37 +1,252 @@
 use std::cmp::min;
 
 use lazy_static::lazy_static;
-use regex::Regex;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
 
-use crate::source::SourceType;
+use crate::graphic::GraphicKind;
+use crate::script::extract_css_strings;
+use crate::source::{SourceType, StyleOrigin};
 
 lazy_static! {
-    static ref STYLE_TAG_REGEX: Regex = Regex::new(
-        r#"(?:<style(?:.+)?>(?P<css>[\S\s]+)</style>|<link(?:.+)href="(?P<css_url>.+)"(?:.+)/?>)"#
-    )
-    .unwrap();
+    static ref SOURCE_SELECTOR: Selector =
+        Selector::parse("style, link[href], meta[content], svg, img[src], script, noscript")
+            .unwrap();
+    static ref CLASS_SELECTOR: Selector = Selector::parse("[class]").unwrap();
+    static ref LINK_SELECTOR: Selector = Selector::parse("a[href]").unwrap();
 }
 
+/// Color sources of a document in document order.
 pub(crate) fn search_html(doc_url: &String, html: &str) -> Vec<SourceType> {
+    let document = Html::parse_document(html);
     let mut sources = Vec::new();
-    for keyword_captures in STYLE_TAG_REGEX.captures_iter(html) {
-        if let Some(css) = keyword_captures.name("css").map(|s| s.as_str().to_string()) {
-            sources.push(SourceType::StyleTagCss { css });
-        }
-        if let Some(css_url) = keyword_captures
-            .name("css_url")
-            .map(|s| s.as_str().to_string())
-        {
-            sources.push(SourceType::LinkedCssFile {
-                url: map_resource_url_to_document(css_url, doc_url),
-            });
+    for element in document.select(&SOURCE_SELECTOR) {
+        match element.value().name() {
+            // styles of inline SVGs are extracted with the SVG
+            "style" if !inside_svg(&element) => sources.push(SourceType::StyleTagCss {
+                css: element.text().collect(),
+                origin: style_origin(&element),
+            }),
+            // with scripting enabled, the parser leaves the fallback markup as text
+            "noscript" => {
+                let fallback: String = element.text().collect();
+                for source in search_html(doc_url, &fallback) {
+                    match source {
+                        SourceType::StyleTagCss { css, .. } => {
+                            sources.push(SourceType::StyleTagCss {
+                                css,
+                                origin: StyleOrigin::Noscript,
+                            })
+                        }
+                        SourceType::LinkedCssFile { .. } => push_stylesheet(&mut sources, source),
+                        _ => {}
+                    }
+                }
+            }
+            "link" => {
+                let url = || {
+                    map_resource_url_to_document(
+                        element.value().attr("href").unwrap().trim().to_string(),
+                        doc_url,
+                    )
+                };
+                let preloaded_style = has_rel(&element, "preload")
+                    && element.value().attr("as").map(str::trim) == Some("style");
+                if (has_rel(&element, "stylesheet") && !has_rel(&element, "alternate"))
+                    || preloaded_style
+                {
+                    push_stylesheet(&mut sources, SourceType::LinkedCssFile { url: url() });
+                } else if has_rel(&element, "manifest") {
+                    sources.push(SourceType::Manifest { url: url() });
+                } else if ["icon", "apple-touch-icon", "mask-icon"]
+                    .iter()
+                    .any(|rel| has_rel(&element, rel))
+                {
+                    sources.push(SourceType::GraphicFile {
+                        url: url(),
+                        kind: GraphicKind::Icon,
+                    });
+                }
+            }
+            "svg" if !inside_svg(&element) => sources.push(SourceType::InlineSvg {
+                svg: element.html(),
+            }),
+            "img" => {
+                // raster images are only fetched for logos, the rest are usually photos
+                let src = element.value().attr("src").unwrap().trim();
+                let kind = image_kind(&element);
+                if kind == GraphicKind::Logo || src.starts_with("data:image/") || is_svg_url(src) {
+                    sources.push(SourceType::GraphicFile {
+                        url: map_resource_url_to_document(src.to_string(), doc_url),
+                        kind,
+                    });
+                }
+            }
+            "meta" => {
+                let content = element.value().attr("content").unwrap();
+                if element.value().attr("property") == Some("og:image") {
+                    sources.push(SourceType::GraphicFile {
+                        url: map_resource_url_to_document(content.trim().to_string(), doc_url),
+                        kind: GraphicKind::OpenGraphImage,
+                    });
+                    continue;
+                }
+                let name = match element.value().attr("name") {
+                    Some(name) => name.to_ascii_lowercase(),
+                    None => continue,
+                };
+                if matches!(name.as_str(), "theme-color" | "msapplication-tilecolor") {
+                    sources.push(SourceType::MetaColor {
+                        name,
+                        content: content.to_string(),
+                        media: element.value().attr("media").map(str::to_string),
+                    });
+                }
+            }
+            "script" if element.value().id() == Some("__NEXT_DATA__") => {
+                let json: String = element.text().collect();
+                for css in extract_css_strings(&json) {
+                    sources.push(SourceType::StyleTagCss {
+                        css,
+                        origin: StyleOrigin::NextData,
+                    });
+                }
+            }
+            "script" if is_javascript(&element) => match element.value().attr("src") {
+                Some(src) => sources.push(SourceType::ScriptFile {
+                    url: map_resource_url_to_document(src.trim().to_string(), doc_url),
+                }),
+                None => sources.push(SourceType::InlineScript {
+                    js: element.text().collect(),
+                }),
+            },
+            _ => {}
         }
     }
+    let classes: Vec<String> = document
+        .select(&CLASS_SELECTOR)
+        .flat_map(|element| {
+            let classes = element.value().attr("class").unwrap_or("");
+            classes.split_ascii_whitespace().map(str::to_string)
+        })
+        .collect();
+    if !classes.is_empty() {
+        sources.push(SourceType::ClassNames { classes });
+    }
     sources
 }
 
-fn map_resource_url_to_document(resource_url: String, document_url: &String) -> String {
-    if resource_url.starts_with("https://") || resource_url.starts_with("http://") {
+/// Absolute HTTP URLs of the documents the `<a>` elements link to, resolved against
+/// `doc_url` and normalized without fragments, in document order.
+pub(crate) fn search_links(doc_url: &str, html: &str) -> Vec<String> {
+    let Ok(base) = Url::parse(doc_url) else {
+        return Vec::new();
+    };
+    let document = Html::parse_document(html);
+    let mut links = Vec::new();
+    for element in document.select(&LINK_SELECTOR) {
+        let href = element.value().attr("href").unwrap().trim();
+        if href.is_empty() || href.starts_with('#') {
+            continue;
+        }
+        let Ok(mut url) = base.join(href) else {
+            continue;
+        };
+        if !matches!(url.scheme(), "http" | "https") {
+            continue;
+        }
+        url.set_fragment(None);
+        let url = url.to_string();
+        if !links.contains(&url) {
+            links.push(url);
+        }
+    }
+    links
+}
+
+/// Adds a linked stylesheet unless it is already linked, as when it is both preloaded and
+/// linked in a `<noscript>` fallback.
+fn push_stylesheet(sources: &mut Vec<SourceType>, stylesheet: SourceType) {
+    if !sources.contains(&stylesheet) {
+        sources.push(stylesheet);
+    }
+}
+
+fn style_origin(element: &ElementRef) -> StyleOrigin {
+    let has_attribute = |prefix: &str| {
+        element
+            .value()
+            .attrs()
+            .any(|(name, _)| name.starts_with(prefix))
+    };
+    let id = element.value().id().unwrap_or("");
+    if has_attribute("data-emotion") {
+        StyleOrigin::Emotion
+    } else if has_attribute("data-styled") {
+        StyleOrigin::StyledComponents
+    } else if id.starts_with("__jsx-") {
+        StyleOrigin::StyledJsx
+    } else if id.starts_with("svelte-") {
+        StyleOrigin::Svelte
+    } else {
+        StyleOrigin::StyleTag
+    }
+}
+
+fn is_javascript(element: &ElementRef) -> bool {
+    match element.value().attr("type") {
+        None => true,
+        Some(script_type) => matches!(
+            script_type.trim().to_ascii_lowercase().as_str(),
+            "" | "module" | "text/javascript" | "application/javascript"
+        ),
+    }
+}
+
+fn inside_svg(element: &ElementRef) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|ancestor| ancestor.value().name() == "svg")
+}
+
+/// Whether the path of `url` ends in `.svg`.
+fn is_svg_url(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.to_ascii_lowercase().ends_with(".svg")
+}
+
+fn image_kind(element: &ElementRef) -> GraphicKind {
+    let mentions_logo = ["src", "alt", "id", "class"].iter().any(|attribute| {
+        element
+            .value()
+            .attr(attribute)
+            .map(|value| value.to_ascii_lowercase().contains("logo"))
+            .unwrap_or(false)
+    });
+    if mentions_logo {
+        GraphicKind::Logo
+    } else {
+        GraphicKind::Image
+    }
+}
+
+fn has_rel(element: &ElementRef, rel: &str) -> bool {
+    element
+        .value()
+        .attr("rel")
+        .map(|rels| {
+            rels.split_ascii_whitespace()
+                .any(|token| token.eq_ignore_ascii_case(rel))
+        })
+        .unwrap_or(false)
+}
+
+pub(crate) fn map_resource_url_to_document(resource_url: String, document_url: &String) -> String {
+    if resource_url.starts_with("https://")
+        || resource_url.starts_with("http://")
+        || resource_url.starts_with("data:")
+    {
         resource_url
     } else {
         let host_start_index = document_url.find("://").unwrap() + 3;
@@ -62,7 +277,6 @@ fn map_resource_url_to_document(resource_url: String, document_url: &String) ->
                 None => Vec::new(),
                 Some(document_filename_slash_index) => document_post_host
                     [0..document_filename_slash_index]
-                    .to_string()
                     .split('/')
                     .map(|s| s.to_string())
                     .collect(),
@@ -101,6 +315,54 @@ fn map_resource_url_to_document(resource_url: String, document_url: &String) ->
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_search_html() {
+        let html = r##"<html><head>
+            <link rel="icon" href="/favicon.ico">
+            <link rel="Stylesheet" href="main.css">
+            <link rel="alternate stylesheet" href="contrast.css" title="High contrast">
+            <link rel="manifest" href="/site.webmanifest">
+            <meta name="theme-color" content="#4285f4" media="(prefers-color-scheme: light)">
+            <meta name="msapplication-TileColor" content="#2b5797">
+            <meta name="description" content="red">
+            <style>body { color: #111 }</style>
+            </head><body><style>p { color: #222 }</style></body></html>"##;
+        let sources = search_html(&String::from("https://host/path/index.html"), html);
+        assert_eq!(
+            vec![
+                SourceType::GraphicFile {
+                    url: String::from("https://host/favicon.ico"),
+                    kind: GraphicKind::Icon
+                },
+                SourceType::LinkedCssFile {
+                    url: String::from("https://host/path/main.css")
+                },
+                SourceType::Manifest {
+                    url: String::from("https://host/site.webmanifest")
+                },
+                SourceType::MetaColor {
+                    name: String::from("theme-color"),
+                    content: String::from("#4285f4"),
+                    media: Some(String::from("(prefers-color-scheme: light)")),
+                },
+                SourceType::MetaColor {
+                    name: String::from("msapplication-tilecolor"),
+                    content: String::from("#2b5797"),
+                    media: None,
+                },
+                SourceType::StyleTagCss {
+                    css: String::from("body { color: #111 }"),
+                    origin: StyleOrigin::StyleTag
+                },
+                SourceType::StyleTagCss {
+                    css: String::from("p { color: #222 }"),
+                    origin: StyleOrigin::StyleTag
+                },
+            ],
+            sources
+        );
+    }
+
     #[test]
     fn test_map_resource_url_to_document_when_at_document_path() {
         assert_map_resource_url(
@@ -184,6 +446,187 @@ mod tests {
         assert_map_resource_url("https://host/file.css", "../../file.css", "https://host");
     }
 
+    #[test]
+    fn test_search_html_graphics() {
+        let html = r##"<html><head>
+            <link rel="icon" href="/favicon.ico">
+            <link rel="icon" href="/favicon.svg?v=2">
+            <link rel="mask-icon" href="/pinned" type="image/svg+xml">
+            <meta property="og:image" content="https://cdn.host/og.png">
+            </head><body>
+            <img src="img/brand.svg" alt="Example">
+            <img src="photo.svg" class="site-logo">
+            <img src="img/Logo.webp">
+            <img src="photo.jpg">
+            <svg viewBox="0 0 1 1"><style>rect { fill: red }</style><svg/></svg>
+            </body></html>"##;
+        let sources = search_html(&String::from("https://host/"), html);
+        assert_eq!(
+            vec![
+                SourceType::GraphicFile {
+                    url: String::from("https://host/favicon.ico"),
+                    kind: GraphicKind::Icon
+                },
+                SourceType::GraphicFile {
+                    url: String::from("https://host/favicon.svg?v=2"),
+                    kind: GraphicKind::Icon
+                },
+                SourceType::GraphicFile {
+                    url: String::from("https://host/pinned"),
+                    kind: GraphicKind::Icon
+                },
+                SourceType::GraphicFile {
+                    url: String::from("https://cdn.host/og.png"),
+                    kind: GraphicKind::OpenGraphImage
+                },
+                SourceType::GraphicFile {
+                    url: String::from("https://host/img/brand.svg"),
+                    kind: GraphicKind::Image
+                },
+                SourceType::GraphicFile {
+                    url: String::from("https://host/photo.svg"),
+                    kind: GraphicKind::Logo
+                },
+                SourceType::GraphicFile {
+                    url: String::from("https://host/img/Logo.webp"),
+                    kind: GraphicKind::Logo
+                },
+                SourceType::InlineSvg {
+                    svg: String::from(
+                        r#"<svg viewBox="0 0 1 1"><style>rect { fill: red }</style><svg></svg></svg>"#
+                    )
+                },
+                SourceType::ClassNames {
+                    classes: vec![String::from("site-logo")]
+                },
+            ],
+            sources
+        );
+    }
+
+    #[test]
+    fn test_search_html_framework_styles() {
+        let html = r##"<html><head>
+            <link rel="preload" href="/_next/static/css/app.css" as="style">
+            <link rel="preload" href="/font.woff2" as="font">
+            <noscript><link rel="stylesheet" href="/_next/static/css/app.css"><style>a{color:red}</style></noscript>
+            <style data-emotion="css 1x2y3z">.css-1x2y3z{color:#111}</style>
+            <style data-styled="active" data-styled-version="5.3.6">.sc-a{color:#222}</style>
+            <style id="__jsx-123">p{color:#333}</style>
+            <style id="svelte-1abc">h1.svelte-1abc{color:#444}</style>
+            </head><body>
+            <script id="__NEXT_DATA__" type="application/json">{"props":{"css":".b{color:#555}","title":"Home"}}</script>
+            </body></html>"##;
+        let sources = search_html(&String::from("https://host/"), html);
+        let style = |css: &str, origin| SourceType::StyleTagCss {
+            css: css.to_string(),
+            origin,
+        };
+        assert_eq!(
+            vec![
+                SourceType::LinkedCssFile {
+                    url: String::from("https://host/_next/static/css/app.css")
+                },
+                style("a{color:red}", StyleOrigin::Noscript),
+                style(".css-1x2y3z{color:#111}", StyleOrigin::Emotion),
+                style(".sc-a{color:#222}", StyleOrigin::StyledComponents),
+                style("p{color:#333}", StyleOrigin::StyledJsx),
+                style("h1.svelte-1abc{color:#444}", StyleOrigin::Svelte),
+                style(".b{color:#555}", StyleOrigin::NextData),
+            ],
+            sources
+        );
+    }
+
+    #[test]
+    fn test_search_html_class_names() {
+        let html = r#"<html><body class="bg-white">
+            <div class="p-4  text-sky-600"><svg class="fill-current"></svg></div>
+            </body></html>"#;
+        let sources = search_html(&String::from("https://host/"), html);
+        assert_eq!(
+            Some(&SourceType::ClassNames {
+                classes: vec![
+                    String::from("bg-white"),
+                    String::from("p-4"),
+                    String::from("text-sky-600"),
+                    String::from("fill-current"),
+                ]
+            }),
+            sources.last()
+        );
+    }
+
+    #[test]
+    fn test_search_links() {
+        let html = r##"<html><body>
+            <a href="/pricing">Pricing</a>
+            <a href="docs/intro.html#install">Docs</a>
+            <a href="docs/intro.html">Docs again</a>
+            <a href="#top">Top</a>
+            <a href="mailto:hi@host">Mail</a>
+            <a href="javascript:void(0)">Menu</a>
+            <a href="https://other.host/">Elsewhere</a>
+            </body></html>"##;
+        assert_eq!(
+            vec![
+                String::from("https://host/pricing"),
+                String::from("https://host/path/docs/intro.html"),
+                String::from("https://other.host/"),
+            ],
+            search_links("https://host/path/index.html", html)
+        );
+    }
+
+    #[test]
+    fn test_search_links_resolution() {
+        let html = r##"<html><body>
+            <a href="./pricing">Pricing</a>
+            <a href="//cdn.example/page">Protocol relative</a>
+            <a href="?page=2">Next</a>
+            <a href="https://HOST/a">Uppercase host</a>
+            <a href="../b/./c/../d">Dot segments</a>
+            </body></html>"##;
+        assert_eq!(
+            vec![
+                String::from("https://host/path/pricing"),
+                String::from("https://cdn.example/page"),
+                String::from("https://host/path/index.html?page=2"),
+                String::from("https://host/a"),
+                String::from("https://host/b/d"),
+            ],
+            search_links("https://host/path/index.html", html)
+        );
+        assert_eq!(
+            vec![String::from("https://host/pricing")],
+            search_links("https://host/", r#"<a href="./pricing">"#)
+        );
+    }
+
+    #[test]
+    fn test_search_html_scripts() {
+        let html = r##"<html><head>
+            <script src="/static/app.js" defer></script>
+            <script type="module">const c = "#fff";</script>
+            <script type="application/ld+json">{"color": "#000"}</script>
+            </head><body><script>var x = 1;</script></body></html>"##;
+        let sources = search_html(&String::from("https://host/"), html);
+        assert_eq!(
+            vec![
+                SourceType::ScriptFile {
+                    url: String::from("https://host/static/app.js")
+                },
+                SourceType::InlineScript {
+                    js: String::from(r##"const c = "#fff";"##)
+                },
+                SourceType::InlineScript {
+                    js: String::from("var x = 1;")
+                },
+            ],
+            sources
+        );
+    }
+
     fn assert_map_resource_url(expected: &str, res_url: &str, doc_url: &str) {
         assert_eq!(
             expected.to_string(),
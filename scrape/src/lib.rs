@@ -1,18 +1,141 @@
+use std::collections::HashMap;
+
+use encoding_rs::Encoding;
 use thiserror::*;
 
-use crate::color::Color;
-use crate::css::extract_colors;
-use crate::html::search_html;
+pub use crate::audit::{ContrastAudit, ContrastCheck, IndistinguishablePair};
+pub use crate::color::Color;
+pub use crate::crawl::{crawl, CrawlOptions, CrawledSite, SitemapSampling};
+use crate::css::{
+    extract_color_declarations, extract_color_pairs, extract_colors, extract_data_uris,
+    extract_page_background, extract_svg_urls,
+};
+use crate::fetch::Fetcher;
+pub use crate::fetch::{CacheOptions, FetchOptions};
+pub use crate::graphic::{Graphic, GraphicKind};
+use crate::html::{map_resource_url_to_document, search_html};
+pub use crate::merge::{MergeOptions, MergedColors, Representative};
+pub use crate::property::PropertyCategory;
+pub use crate::quantize::{QuantizeMethod, QuantizeOptions, WeightedColor};
+pub use crate::roles::{Role, RoleCandidate};
+use crate::script::extract_script_colors;
+pub use crate::script::ScriptColor;
 use crate::source::SourceType;
+pub use crate::source::StyleOrigin;
+use crate::tailwind::extract_utility_colors;
+pub use crate::tailwind::UtilityColor;
+use crate::theme::{manifest_theme_colors, meta_theme_color};
+pub use crate::theme::{ThemeColor, ThemeColorSource};
 
-mod color;
+mod audit;
+pub mod color;
+mod crawl;
 mod css;
+mod data_uri;
+mod fetch;
+mod graphic;
 mod html;
+mod merge;
+mod property;
+mod quantize;
+mod roles;
+mod script;
 mod source;
+mod tailwind;
+mod theme;
 
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SiteColors {
     pub url: String,
     pub colors: Vec<Color>,
+    pub pairs: Vec<ColorPair>,
+    pub declarations: Vec<ColorDeclaration>,
+    /// Colors declared by `<meta>` tags and the web app manifest.
+    pub theme_colors: Vec<ThemeColor>,
+    /// Logos, icons and other images, whose colors are not part of `colors`.
+    pub graphics: Vec<Graphic>,
+    /// Colors found in scripts, which are less certain and not part of `colors`.
+    pub script_colors: Vec<ScriptColor>,
+    /// Colors of Tailwind CSS utility classes, when [`ScrapeOptions::tailwind_classes`]
+    /// is set.
+    pub utility_colors: Vec<UtilityColor>,
+    /// Background color of the `body` or root element, when a stylesheet declares one.
+    pub page_background: Option<Color>,
+    /// Translucent colors composited over the page background, when
+    /// [`ScrapeOptions::flatten_translucent`] is set.
+    pub effective_colors: Vec<EffectiveColor>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ScrapeOptions {
+    /// Reports the opaque color translucent colors appear as over the page background.
+    pub flatten_translucent: bool,
+    /// Scans inline and linked scripts for colors of CSS-in-JS libraries. Off by default,
+    /// since it downloads every script bundle and its colors are less certain.
+    pub scan_scripts: bool,
+    /// Maps Tailwind CSS color utilities in `class` attributes to colors. Off by default,
+    /// since other class names can look like utilities.
+    pub tailwind_classes: bool,
+    pub fetch: FetchOptions,
+}
+
+/// Text and background colors declared together by a CSS rule.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorPair {
+    pub selector: String,
+    pub foreground: Color,
+    pub background: Color,
+}
+
+/// A color used by a CSS declaration, with the rule's selector for context.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorDeclaration {
+    pub selector: String,
+    /// Lowercased property name, including custom properties such as `--brand`.
+    pub property: String,
+    pub category: PropertyCategory,
+    pub color: Color,
+    pub origin: StyleOrigin,
+}
+
+/// A translucent color and the opaque color it appears as over the page background.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EffectiveColor {
+    pub color: Color,
+    pub effective: Color,
+}
+
+impl SiteColors {
+    /// Composites `color` over the page background, or over white when none was found.
+    pub fn effective_color(&self, color: &Color) -> Color {
+        let background = self
+            .page_background
+            .map(|background| background.over(&WHITE))
+            .unwrap_or(WHITE);
+        color.over(&background)
+    }
+
+    /// Distinct colors with the number of sources, such as stylesheets and `<style>` tags,
+    /// they were found in, in the order first seen. A source counts once however often it
+    /// uses a color, so this measures spread rather than usage.
+    pub fn color_counts(&self) -> Vec<(Color, usize)> {
+        let mut indices: HashMap<Color, usize> = HashMap::new();
+        let mut counts: Vec<(Color, usize)> = Vec::new();
+        for color in &self.colors {
+            match indices.get(color) {
+                Some(i) => counts[*i].1 += 1,
+                None => {
+                    indices.insert(*color, counts.len());
+                    counts.push((*color, 1));
+                }
+            }
+        }
+        counts
+    }
 }
 
 #[derive(Error, Debug)]
@@ -21,50 +144,226 @@ pub enum ColorScrapeError {
     FetchDocumentError(#[from] reqwest::Error),
     #[error("bad http response for doc")]
     BadDocumentResponse(),
+    #[error("robots.txt disallows fetching {0}")]
+    DisallowedByRobots(String),
+    #[error("{0} is not cached for an offline scrape")]
+    NotCached(String),
+    #[error("{0} is larger than the size limit")]
+    DocumentTooLarge(String),
 }
 
+/// Largest graphic file downloaded, as raster graphics are decoded in full.
+const MAX_GRAPHIC_SIZE: usize = 5 * 1024 * 1024;
+
+const WHITE: Color = Color {
+    r: 255,
+    g: 255,
+    b: 255,
+    a: 255,
+};
+
 pub async fn scrape(url: String) -> Result<SiteColors, ColorScrapeError> {
-    let mut colors = Vec::new();
-    let html = fetch(&url).await?;
-    for color_source in search_html(&url, html.as_str()) {
-        match color_source {
-            SourceType::StyleTagCss { css } => {
-                for color in extract_colors(css.as_str()) {
-                    colors.push(color);
+    scrape_with_options(url, &ScrapeOptions::default()).await
+}
+
+pub async fn scrape_with_options(
+    url: String,
+    options: &ScrapeOptions,
+) -> Result<SiteColors, ColorScrapeError> {
+    let fetcher = Fetcher::new(&options.fetch)?;
+    let (html, encoding) = fetcher.html(&url).await?;
+    let mut site_colors = SiteColors {
+        url: url.clone(),
+        ..Default::default()
+    };
+    for (_, contribution) in scrape_sources(&url, &html, encoding, options, &fetcher).await? {
+        site_colors.merge(contribution);
+    }
+    site_colors.finish(options);
+    Ok(site_colors)
+}
+
+/// Colors of each color source of a document, with a key identifying the source across
+/// the pages of a site. Stylesheets that declare no encoding are decoded with the
+/// document's `encoding`.
+async fn scrape_sources(
+    url: &String,
+    html: &str,
+    encoding: &'static Encoding,
+    options: &ScrapeOptions,
+    fetcher: &Fetcher,
+) -> Result<Vec<(String, SiteColors)>, ColorScrapeError> {
+    let mut contributions = Vec::new();
+    for color_source in search_html(url, html) {
+        let key = color_source.key(url);
+        let mut contribution = SiteColors::default();
+        let (css, css_url, origin) = match color_source {
+            SourceType::StyleTagCss { css, origin } => (css, url.clone(), origin),
+            SourceType::LinkedCssFile { url } => match fetcher.css(&url, encoding).await {
+                Ok(css) => (css, url, StyleOrigin::Stylesheet),
+                // like other subresources, a stylesheet robots.txt disallows is skipped
+                Err(ColorScrapeError::DisallowedByRobots(_)) => {
+                    contributions.push((key, contribution));
+                    continue;
                 }
+                Err(error) => return Err(error),
+            },
+            SourceType::MetaColor {
+                name,
+                content,
+                media,
+            } => {
+                contribution
+                    .theme_colors
+                    .extend(meta_theme_color(&name, &content, media));
+                contributions.push((key, contribution));
+                continue;
             }
-            SourceType::LinkedCssFile { url } => {
-                for color in extract_colors(fetch(&url).await?.as_str()) {
-                    colors.push(color);
+            SourceType::Manifest { url } => {
+                // a missing or broken manifest shouldn't fail the scrape
+                if let Ok(manifest) = fetcher.text(&url).await {
+                    contribution.theme_colors = manifest_theme_colors(&manifest);
                 }
+                contributions.push((key, contribution));
+                continue;
             }
+            SourceType::InlineSvg { svg } => {
+                contribution
+                    .graphics
+                    .push(Graphic::from_svg(None, GraphicKind::InlineSvg, &svg));
+                contributions.push((key, contribution));
+                continue;
+            }
+            SourceType::GraphicFile { url, kind } => {
+                fetch_graphic(fetcher, &mut contribution.graphics, url, kind).await;
+                contributions.push((key, contribution));
+                continue;
+            }
+            SourceType::InlineScript { js } => {
+                if options.scan_scripts {
+                    contribution.script_colors = extract_script_colors(&js, None);
+                }
+                contributions.push((key, contribution));
+                continue;
+            }
+            SourceType::ClassNames { classes } => {
+                if options.tailwind_classes {
+                    contribution.utility_colors = extract_utility_colors(&classes);
+                }
+                contributions.push((key, contribution));
+                continue;
+            }
+            SourceType::ScriptFile { url } => {
+                // scripts are often third party, so failing ones are skipped
+                if options.scan_scripts {
+                    if let Ok(js) = fetcher.text(&url).await {
+                        contribution.script_colors = extract_script_colors(&js, Some(&url));
+                    }
+                }
+                contributions.push((key, contribution));
+                continue;
+            }
+        };
+        let graphics = &mut contribution.graphics;
+        for data_uri in extract_data_uris(&css) {
+            fetch_graphic(fetcher, graphics, data_uri, GraphicKind::CssImage).await;
+        }
+        for svg_url in extract_svg_urls(&css) {
+            let svg_url = map_resource_url_to_document(svg_url, &css_url);
+            fetch_graphic(fetcher, graphics, svg_url, GraphicKind::CssImage).await;
         }
+        contribution.colors = extract_colors(css.as_str());
+        contribution.pairs = extract_color_pairs(css.as_str());
+        contribution.declarations = extract_color_declarations(css.as_str())
+            .into_iter()
+            .map(|declaration| ColorDeclaration {
+                origin,
+                ..declaration
+            })
+            .collect();
+        contribution.page_background = extract_page_background(css.as_str());
+        contributions.push((key, contribution));
     }
-
-    Ok(SiteColors { url, colors })
+    Ok(contributions)
 }
 
-async fn fetch(url: &String) -> Result<String, ColorScrapeError> {
-    let result = reqwest::get(url).await;
-    let response = match result {
-        Ok(response) => response,
-        Err(err) => {
-            return Err(ColorScrapeError::FetchDocumentError(err));
+impl SiteColors {
+    /// Adds the colors of a later source, whose page background takes precedence.
+    fn merge(&mut self, other: SiteColors) {
+        self.colors.extend(other.colors);
+        self.pairs.extend(other.pairs);
+        self.declarations.extend(other.declarations);
+        self.theme_colors.extend(other.theme_colors);
+        for graphic in other.graphics {
+            let duplicate =
+                graphic.url.is_some() && self.graphics.iter().any(|added| added.url == graphic.url);
+            if !duplicate {
+                self.graphics.push(graphic);
+            }
+        }
+        self.script_colors.extend(other.script_colors);
+        for utility in other.utility_colors {
+            match self
+                .utility_colors
+                .iter_mut()
+                .find(|added| added.utility == utility.utility)
+            {
+                Some(added) => added.count += utility.count,
+                None => self.utility_colors.push(utility),
+            }
+        }
+        if other.page_background.is_some() {
+            self.page_background = other.page_background;
         }
-    };
-    let status_code = response.status().as_u16();
-    if !(200..300).contains(&status_code) {
-        return Err(ColorScrapeError::BadDocumentResponse());
     }
-    let response_body = match response.text().await {
-        Ok(response_body) => response_body,
-        Err(err) => return Err(ColorScrapeError::FetchDocumentError(err)),
-    };
-    Ok(response_body)
+
+    /// Derives the colors that depend on every source.
+    fn finish(&mut self, options: &ScrapeOptions) {
+        if options.flatten_translucent {
+            self.effective_colors = self
+                .color_counts()
+                .into_iter()
+                .filter(|(color, _)| !color.is_opaque())
+                .map(|(color, _)| EffectiveColor {
+                    color,
+                    effective: self.effective_color(&color),
+                })
+                .collect();
+        }
+    }
+}
+
+/// Adds the graphic at `url` unless it was already added, ignoring images that fail to load
+/// or decode. Data URIs are decoded in place.
+async fn fetch_graphic(
+    fetcher: &Fetcher,
+    graphics: &mut Vec<Graphic>,
+    url: String,
+    kind: GraphicKind,
+) {
+    if url.starts_with("data:") {
+        graphics.extend(Graphic::from_data_uri(kind, &url));
+        return;
+    }
+    let url = url.split('#').next().unwrap_or(&url).to_string();
+    if graphics
+        .iter()
+        .any(|graphic| graphic.url.as_ref() == Some(&url))
+    {
+        return;
+    }
+    if let Ok(bytes) = fetcher.bytes(&url, MAX_GRAPHIC_SIZE).await {
+        graphics.extend(Graphic::from_bytes(Some(url), kind, &bytes));
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use warp::Filter;
+
     use super::*;
 
     fn run_web_server(port: u16) -> impl Fn(&str) -> String {
@@ -72,6 +371,35 @@ mod tests {
         move |filename: &str| format!("http://localhost:{port}/{filename}")
     }
 
+    /// A request to `run_logging_web_server`.
+    #[derive(Debug)]
+    struct LoggedRequest {
+        path: String,
+        /// Sent `If-None-Match` or `If-Modified-Since`.
+        conditional: bool,
+        status: u16,
+    }
+
+    /// Like `run_web_server`, also recording every request.
+    fn run_logging_web_server(
+        port: u16,
+    ) -> (impl Fn(&str) -> String, Arc<Mutex<Vec<LoggedRequest>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let routes = warp::fs::dir("./examples/web/").with(warp::log::custom(move |info| {
+            let headers = info.request_headers();
+            log.lock().unwrap().push(LoggedRequest {
+                path: info.path().to_string(),
+                conditional: headers.contains_key("if-none-match")
+                    || headers.contains_key("if-modified-since"),
+                status: info.status().as_u16(),
+            });
+        }));
+        tokio::spawn(warp::serve(routes).run(([127, 0, 0, 1], port)));
+        let url = move |filename: &str| format!("http://localhost:{port}/{filename}");
+        (url, requests)
+    }
+
     #[tokio::test]
     async fn test_style_tag_css() {
         let url = run_web_server(5100);
@@ -94,6 +422,10 @@ mod tests {
         match result {
             Ok(colors) => {
                 assert_eq!(3, colors.colors.len());
+                assert_eq!(1, colors.pairs.len());
+                assert_eq!("div", colors.pairs[0].selector);
+                assert_eq!(3, colors.declarations.len());
+                assert_eq!("border-color", colors.declarations[1].property);
             }
             Err(err) => {
                 println!("{err}");
@@ -101,4 +433,481 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_flatten_translucent() {
+        let url = run_web_server(5102);
+        let options = ScrapeOptions {
+            flatten_translucent: true,
+            ..Default::default()
+        };
+        let colors = scrape_with_options(url("translucent.html"), &options)
+            .await
+            .unwrap();
+        assert_eq!(Some(Color::rgb(0xf0, 0xf0, 0xf0)), colors.page_background);
+        assert_eq!(
+            vec![EffectiveColor {
+                color: Color::rgba(0, 0, 0, 128),
+                effective: Color::rgb(0x78, 0x78, 0x78),
+            }],
+            colors.effective_colors
+        );
+        let unflattened = scrape(url("translucent.html")).await.unwrap();
+        assert!(unflattened.effective_colors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_theme_colors() {
+        let url = run_web_server(5103);
+        let colors = scrape(url("theme.html")).await.unwrap();
+        assert_eq!(
+            vec![
+                ThemeColor {
+                    color: Color::rgb(0xe4, 0x00, 0x2b),
+                    source: ThemeColorSource::MetaThemeColor,
+                    media: Some(String::from("(prefers-color-scheme: light)")),
+                },
+                ThemeColor {
+                    color: Color::rgb(0x7a, 0x00, 0x19),
+                    source: ThemeColorSource::MetaThemeColor,
+                    media: Some(String::from("(prefers-color-scheme: dark)")),
+                },
+                ThemeColor {
+                    color: Color::rgb(0xe4, 0x00, 0x2b),
+                    source: ThemeColorSource::TileColor,
+                    media: None,
+                },
+                ThemeColor {
+                    color: Color::rgb(0xe4, 0x00, 0x2b),
+                    source: ThemeColorSource::ManifestThemeColor,
+                    media: None,
+                },
+                ThemeColor {
+                    color: Color::rgb(0xff, 0xff, 0xff),
+                    source: ThemeColorSource::ManifestBackgroundColor,
+                    media: None,
+                },
+            ],
+            colors.theme_colors
+        );
+        assert_eq!(
+            vec![Color::rgb(0xe4, 0x00, 0x2b), Color::rgb(0x7a, 0x00, 0x19)],
+            colors.brand_colors()
+        );
+        assert!(colors.colors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_svg_graphics() {
+        let url = run_web_server(5104);
+        let colors = scrape(url("svg.html")).await.unwrap();
+        let graphics: Vec<(Option<String>, GraphicKind)> = colors
+            .graphics
+            .iter()
+            .map(|graphic| (graphic.url.clone(), graphic.kind))
+            .collect();
+        assert_eq!(
+            vec![
+                (Some(url("favicon.svg")), GraphicKind::Icon),
+                (Some(url("pattern.svg")), GraphicKind::CssImage),
+                (Some(url("logo.svg")), GraphicKind::Logo),
+                (None, GraphicKind::InlineSvg),
+            ],
+            graphics
+        );
+        assert_eq!(
+            vec![
+                Color::rgb(0xe4, 0x00, 0x2b),
+                Color::rgb(0xf5, 0xf5, 0xf5),
+                Color::rgb(0xff, 0xff, 0xff),
+                Color::rgb(0x11, 0x11, 0x11),
+            ],
+            colors.graphic_colors()
+        );
+        assert_eq!(vec![Color::rgb(0x11, 0x11, 0x11)], colors.colors);
+    }
+
+    #[tokio::test]
+    async fn test_raster_graphics() {
+        let url = run_web_server(5105);
+        let colors = scrape(url("raster.html")).await.unwrap();
+        let graphics: Vec<(String, GraphicKind, Color, f64)> = colors
+            .graphics
+            .iter()
+            .map(|graphic| {
+                let dominant = graphic.colors[0];
+                let url = graphic.url.clone().unwrap();
+                (url, graphic.kind, dominant.color, dominant.weight)
+            })
+            .collect();
+        assert_eq!(3, graphics.len());
+        assert_eq!(
+            (
+                url("favicon.ico"),
+                GraphicKind::Icon,
+                Color::rgb(0x1a, 0x23, 0x7e),
+                1.
+            ),
+            graphics[0]
+        );
+        assert_eq!(url("og.jpg"), graphics[1].0);
+        assert_eq!(GraphicKind::OpenGraphImage, graphics[1].1);
+        assert!(graphics[1].2.delta_e_2000(&Color::rgb(0, 0x80, 0x80)) < 2.);
+        assert_eq!(
+            (
+                url("logo.png"),
+                GraphicKind::Logo,
+                Color::rgb(0xe4, 0x00, 0x2b),
+                0.75
+            ),
+            graphics[2]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_data_uri_graphics() {
+        let url = run_web_server(5106);
+        let colors = scrape(url("data-uri.html")).await.unwrap();
+        let graphics: Vec<(GraphicKind, Color)> = colors
+            .graphics
+            .iter()
+            .map(|graphic| (graphic.kind, graphic.colors[0].color))
+            .collect();
+        assert_eq!(
+            vec![
+                (GraphicKind::Icon, Color::rgb(0, 0, 0x80)),
+                (GraphicKind::CssImage, Color::rgb(0xe4, 0x00, 0x2b)),
+                (GraphicKind::CssImage, Color::rgb(0xe4, 0x00, 0x2b)),
+                (GraphicKind::Logo, Color::rgb(0x1a, 0x23, 0x7e)),
+            ],
+            graphics
+        );
+        assert_eq!(2, colors.graphics[2].colors.len());
+        assert_eq!(vec![Color::rgb(0x11, 0x11, 0x11)], colors.colors);
+    }
+
+    #[tokio::test]
+    async fn test_script_colors() {
+        let url = run_web_server(5107);
+        let options = ScrapeOptions {
+            scan_scripts: true,
+            ..Default::default()
+        };
+        let colors = scrape_with_options(url("css-in-js.html"), &options)
+            .await
+            .unwrap();
+        let script_colors: Vec<(Color, Option<String>, Option<String>)> = colors
+            .script_colors
+            .iter()
+            .map(|c| (c.color, c.property.clone(), c.url.clone()))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    Color::rgb(0xe4, 0x00, 0x2b),
+                    Some(String::from("background")),
+                    Some(url("bundle.js"))
+                ),
+                (
+                    Color::rgb(0xff, 0xff, 0xff),
+                    Some(String::from("color")),
+                    Some(url("bundle.js"))
+                ),
+                (
+                    Color::rgb(0x1a, 0x23, 0x7e),
+                    Some(String::from("accent")),
+                    None
+                ),
+            ],
+            script_colors
+        );
+        assert!(colors.script_colors.iter().all(|c| c.confidence < 1.));
+        assert!(colors.colors.is_empty());
+
+        let unscanned = scrape(url("css-in-js.html")).await.unwrap();
+        assert!(unscanned.script_colors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_framework_styles() {
+        let url = run_web_server(5108);
+        let colors = scrape(url("framework.html")).await.unwrap();
+        let origins: Vec<StyleOrigin> = colors.declarations.iter().map(|d| d.origin).collect();
+        assert_eq!(
+            vec![
+                StyleOrigin::Stylesheet,
+                StyleOrigin::Stylesheet,
+                StyleOrigin::Stylesheet,
+                StyleOrigin::Emotion,
+                StyleOrigin::NextData,
+            ],
+            origins
+        );
+        assert_eq!(Color::rgb(0x1a, 0x23, 0x7e), colors.declarations[4].color);
+    }
+
+    #[tokio::test]
+    async fn test_tailwind_classes() {
+        let url = run_web_server(5109);
+        let colors = scrape(url("tailwind.html")).await.unwrap();
+        assert!(colors.utility_colors.is_empty());
+
+        let options = ScrapeOptions {
+            tailwind_classes: true,
+            ..Default::default()
+        };
+        let colors = scrape_with_options(url("tailwind.html"), &options)
+            .await
+            .unwrap();
+        let utilities: Vec<(&str, Color, usize)> = colors
+            .utility_colors
+            .iter()
+            .map(|c| (c.utility.as_str(), c.color, c.count))
+            .collect();
+        assert_eq!(
+            vec![
+                ("bg-slate-50", Color::rgb(0xf8, 0xfa, 0xfc), 1),
+                ("text-slate-900", Color::rgb(0x0f, 0x17, 0x2a), 1),
+                ("text-[#1da1f2]", Color::rgb(0x1d, 0xa1, 0xf2), 2),
+                ("border-red-600/50", Color::rgba(0xdc, 0x26, 0x26, 128), 1),
+            ],
+            utilities
+        );
+    }
+
+    #[tokio::test]
+    async fn test_crawl() {
+        let url = run_web_server(5110);
+        let options = CrawlOptions {
+            max_depth: 1,
+            ..Default::default()
+        };
+        let crawled = crawl(url("crawl/index.html"), &options).await.unwrap();
+        let pages: Vec<&str> = crawled.pages.iter().map(|p| p.url.as_str()).collect();
+        assert_eq!(
+            vec![
+                url("crawl/index.html"),
+                url("crawl/pricing.html"),
+                url("crawl/docs.html"),
+            ],
+            pages
+        );
+        assert_eq!(2, crawled.pages[0].colors.len());
+        assert_eq!(3, crawled.pages[1].colors.len());
+        assert_eq!(
+            vec![
+                Color::rgb(0xfa, 0xfa, 0xfa),
+                Color::rgb(0x17, 0x17, 0x17),
+                Color::rgb(0x16, 0xa3, 0x4a),
+                Color::rgb(0xc0, 0x26, 0xd3),
+            ],
+            crawled.site.colors
+        );
+        assert_eq!(
+            Some(Color::rgb(0xfa, 0xfa, 0xfa)),
+            crawled.site.page_background
+        );
+
+        let options = CrawlOptions {
+            max_pages: 10,
+            ..Default::default()
+        };
+        let crawled = crawl(url("crawl/index.html"), &options).await.unwrap();
+        assert_eq!(4, crawled.pages.len());
+        assert_eq!(url("crawl/checkout.html"), crawled.pages[3].url);
+
+        let options = CrawlOptions {
+            max_pages: 1,
+            ..Default::default()
+        };
+        let crawled = crawl(url("crawl/index.html"), &options).await.unwrap();
+        assert_eq!(1, crawled.pages.len());
+        assert!(crawl(url("crawl/missing.html"), &options).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_crawl_sitemap() {
+        let (url, requests) = run_logging_web_server(5111);
+        let options = CrawlOptions {
+            max_depth: 0,
+            sitemap: Some(SitemapSampling::First),
+            ..Default::default()
+        };
+        let crawled = crawl(url("crawl/index.html"), &options).await.unwrap();
+        let pages: Vec<&str> = crawled.pages.iter().map(|p| p.url.as_str()).collect();
+        assert_eq!(
+            vec![
+                url("crawl/index.html"),
+                url("crawl/checkout.html"),
+                url("crawl/docs.html"),
+            ],
+            pages
+        );
+        // read for its sitemaps and its rules, but requested once
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            1,
+            requests.iter().filter(|r| r.path == "/robots.txt").count(),
+            "{requests:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_robots_txt() {
+        let url = run_web_server(5112);
+        let result = scrape(url("crawl/private/index.html")).await;
+        assert!(matches!(
+            result,
+            Err(ColorScrapeError::DisallowedByRobots(_))
+        ));
+        let options = ScrapeOptions {
+            fetch: FetchOptions {
+                respect_robots_txt: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let colors = scrape_with_options(url("crawl/private/index.html"), &options)
+            .await
+            .unwrap();
+        assert_eq!(vec![Color::rgb(0xfe, 0xf9, 0xc3)], colors.colors);
+        // a disallowed stylesheet is skipped rather than failing the page
+        let colors = scrape(url("crawl/preview.html")).await.unwrap();
+        assert_eq!(vec![Color::rgb(0x7c, 0x3a, 0xed)], colors.colors);
+        // nothing listens on this port, so robots.txt is unreachable
+        let result = scrape(String::from("http://localhost:5199/index.html")).await;
+        assert!(matches!(
+            result,
+            Err(ColorScrapeError::FetchDocumentError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_delay() {
+        let url = run_web_server(5113);
+        let options = CrawlOptions {
+            max_depth: 1,
+            scrape: ScrapeOptions {
+                fetch: FetchOptions {
+                    user_agent: String::from("slow_scraper/1.0"),
+                    min_delay: Duration::ZERO,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let start = std::time::Instant::now();
+        let crawled = crawl(url("crawl/index.html"), &options).await.unwrap();
+        // the three pages and their shared stylesheet start 250ms apart
+        assert!(start.elapsed() >= Duration::from_millis(750));
+        assert_eq!(3, crawled.pages.len());
+    }
+
+    #[tokio::test]
+    async fn test_http_cache() {
+        let (url, requests) = run_logging_web_server(5114);
+        let dir = std::env::temp_dir().join(format!("color_scrape_cache_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let options = |offline| ScrapeOptions {
+            fetch: FetchOptions {
+                cache: Some(CacheOptions {
+                    offline,
+                    ..CacheOptions::new(&dir)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let online = scrape_with_options(url("linked-css.html"), &options(false))
+            .await
+            .unwrap();
+        // the page, its stylesheet and robots.txt
+        assert_eq!(6, std::fs::read_dir(&dir).unwrap().count());
+        assert!(requests.lock().unwrap().iter().all(|r| !r.conditional));
+        requests.lock().unwrap().clear();
+        // without a max-age, the second scrape revalidates each response and the server
+        // answers 304 Not Modified
+        let revalidated = scrape_with_options(url("linked-css.html"), &options(false))
+            .await
+            .unwrap();
+        assert_eq!(online.colors, revalidated.colors);
+        {
+            let requests = requests.lock().unwrap();
+            assert_eq!(3, requests.len(), "{requests:?}");
+            assert!(
+                requests.iter().all(|r| r.conditional && r.status == 304),
+                "{requests:?}"
+            );
+        }
+        assert_eq!(6, std::fs::read_dir(&dir).unwrap().count());
+        requests.lock().unwrap().clear();
+
+        let offline = scrape_with_options(url("linked-css.html"), &options(true))
+            .await
+            .unwrap();
+        assert_eq!(online.colors, offline.colors);
+        assert!(requests.lock().unwrap().is_empty());
+        assert!(matches!(
+            scrape_with_options(url("style-tag.html"), &options(true)).await,
+            Err(ColorScrapeError::NotCached(_))
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_non_utf8_documents() {
+        let url = run_web_server(5115);
+        let colors = scrape(url("latin1.html")).await.unwrap();
+        let selectors: Vec<&str> = colors
+            .declarations
+            .iter()
+            .map(|declaration| declaration.selector.as_str())
+            .collect();
+        // the page's meta charset, the page's encoding as the fallback of a stylesheet,
+        // an @charset rule and a BOM
+        for selector in [".crème", ".café", ".€uro", ".naïve"] {
+            assert!(selectors.contains(&selector), "{selectors:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_graphic_size() {
+        let url = run_web_server(5116);
+        let fetcher = Fetcher::new(&FetchOptions::default()).unwrap();
+        let logo = url("logo.png");
+        assert!(matches!(
+            fetcher.bytes(&logo, 16).await,
+            Err(ColorScrapeError::DocumentTooLarge(_))
+        ));
+        let bytes = fetcher.bytes(&logo, MAX_GRAPHIC_SIZE).await.unwrap();
+        // a document already fetched is held to the smaller limit too
+        assert!(bytes.len() > 16);
+        assert!(matches!(
+            fetcher.bytes(&logo, 16).await,
+            Err(ColorScrapeError::DocumentTooLarge(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_shares_concurrent_requests() {
+        let (url, requests) = run_logging_web_server(5117);
+        let options = CrawlOptions {
+            max_depth: 1,
+            ..Default::default()
+        };
+        let crawled = crawl(url("shared/index.html"), &options).await.unwrap();
+        // both pages are scraped at once and link the same stylesheet
+        assert_eq!(3, crawled.pages.len());
+        assert_eq!(
+            vec![Color::rgb(0x1e, 0x29, 0x3b), Color::rgb(0xf8, 0xfa, 0xfc)],
+            crawled.site.colors
+        );
+        let requests = requests.lock().unwrap();
+        let stylesheet_requests = requests
+            .iter()
+            .filter(|request| request.path == "/shared/shared.css")
+            .count();
+        assert_eq!(1, stylesheet_requests, "{requests:?}");
+    }
 }
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+
+use crate::color::{Color, DifferenceMetric, Oklab};
+use crate::SiteColors;
+
+/// How a merged cluster of similar colors is represented in the result.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Representative {
+    /// The color with the highest count, with ties going to the color seen first. For
+    /// [`SiteColors::merge_similar`] that is the color found in the most sources.
+    MostFrequent,
+    /// Count weighted average of the cluster in OKLab.
+    Centroid,
+    /// The color of the cluster that was seen first.
+    Original,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MergeOptions {
+    pub metric: DifferenceMetric,
+    /// Colors within this difference of a cluster's highest counted color join the cluster.
+    pub threshold: f64,
+    pub representative: Representative,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            metric: DifferenceMetric::Ciede2000,
+            threshold: 2.3,
+            representative: Representative::MostFrequent,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MergedColors {
+    /// One color per cluster, in the order clusters were first seen.
+    pub colors: Vec<Color>,
+    /// Maps every distinct input color to the color representing its cluster. Serialized
+    /// as a list of `{"original", "representative"}` pairs, since colors can't be map keys
+    /// in every representation.
+    #[cfg_attr(feature = "serde", serde(with = "representative_pairs"))]
+    pub representatives: HashMap<Color, Color>,
+}
+
+#[cfg(feature = "serde")]
+mod representative_pairs {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::color::Color;
+
+    #[derive(Deserialize, Serialize)]
+    struct Pair {
+        original: Color,
+        representative: Color,
+    }
+
+    pub fn serialize<S: Serializer>(
+        representatives: &HashMap<Color, Color>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut pairs: Vec<Pair> = representatives
+            .iter()
+            .map(|(original, representative)| Pair {
+                original: *original,
+                representative: *representative,
+            })
+            .collect();
+        // a stable order, unlike the map's
+        pairs.sort_by_key(|pair| {
+            let Color { r, g, b, a } = pair.original;
+            (r, g, b, a)
+        });
+        pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<Color, Color>, D::Error> {
+        let pairs = Vec::<Pair>::deserialize(deserializer)?;
+        Ok(pairs
+            .into_iter()
+            .map(|pair| (pair.original, pair.representative))
+            .collect())
+    }
+}
+
+struct Cluster {
+    first_seen: usize,
+    members: Vec<(usize, Color, usize)>,
+}
+
+impl SiteColors {
+    /// Merges colors counted by [`color_counts`](Self::color_counts).
+    pub fn merge_similar(&self, options: &MergeOptions) -> MergedColors {
+        merge_similar(&self.color_counts(), options)
+    }
+}
+
+/// Clusters `(color, count)` pairs given in the order the colors were seen.
+pub fn merge_similar(color_counts: &[(Color, usize)], options: &MergeOptions) -> MergedColors {
+    let mut by_usage: Vec<(usize, Color, usize)> = color_counts
+        .iter()
+        .enumerate()
+        .map(|(i, (color, count))| (i, *color, *count))
+        .collect();
+    by_usage.sort_by_key(|(_, _, count)| std::cmp::Reverse(*count));
+
+    let mut clusters: Vec<Cluster> = Vec::new();
+    for member in by_usage {
+        let nearest = clusters
+            .iter_mut()
+            .map(|cluster| {
+                let difference = cluster.members[0].1.difference(&member.1, options.metric);
+                (difference, cluster)
+            })
+            .filter(|(difference, _)| *difference <= options.threshold)
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+        match nearest {
+            Some((_, cluster)) => {
+                cluster.first_seen = cluster.first_seen.min(member.0);
+                cluster.members.push(member);
+            }
+            None => clusters.push(Cluster {
+                first_seen: member.0,
+                members: vec![member],
+            }),
+        }
+    }
+    clusters.sort_by_key(|cluster| cluster.first_seen);
+
+    let mut colors = Vec::with_capacity(clusters.len());
+    let mut representatives = HashMap::new();
+    for cluster in clusters {
+        let representative = match options.representative {
+            Representative::MostFrequent => cluster.members[0].1,
+            Representative::Original => {
+                cluster
+                    .members
+                    .iter()
+                    .min_by_key(|(first_seen, _, _)| *first_seen)
+                    .unwrap()
+                    .1
+            }
+            Representative::Centroid => centroid(&cluster.members),
+        };
+        for (_, color, _) in &cluster.members {
+            representatives.insert(*color, representative);
+        }
+        colors.push(representative);
+    }
+    MergedColors {
+        colors,
+        representatives,
+    }
+}
+
+fn centroid(members: &[(usize, Color, usize)]) -> Color {
+    if members.len() == 1 {
+        return members[0].1;
+    }
+    let (mut l, mut a, mut b, mut alpha, mut total) = (0., 0., 0., 0., 0.);
+    for (_, color, count) in members {
+        let weight = *count as f64;
+        let oklab = color.to_oklab();
+        l += oklab.l * weight;
+        a += oklab.a * weight;
+        b += oklab.b * weight;
+        alpha += color.a as f64 * weight;
+        total += weight;
+    }
+    let mut color = Oklab {
+        l: l / total,
+        a: a / total,
+        b: b / total,
+    }
+    .to_color();
+    color.a = (alpha / total).round() as u8;
+    color
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(colors: &[(Color, usize)], representative: Representative) -> MergedColors {
+        merge_similar(
+            colors,
+            &MergeOptions {
+                representative,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_merges_near_duplicate_grays() {
+        let colors = [
+            (Color::rgb(0x32, 0x32, 0x32), 1),
+            (Color::rgb(0x33, 0x33, 0x33), 3),
+            (Color::rgb(0x34, 0x34, 0x34), 2),
+            (Color::rgb(0xff, 0x00, 0x00), 1),
+        ];
+        let merged = merge(&colors, Representative::MostFrequent);
+        assert_eq!(
+            vec![Color::rgb(0x33, 0x33, 0x33), Color::rgb(0xff, 0x00, 0x00)],
+            merged.colors
+        );
+        assert_eq!(4, merged.representatives.len());
+        assert_eq!(
+            Color::rgb(0x33, 0x33, 0x33),
+            merged.representatives[&Color::rgb(0x34, 0x34, 0x34)]
+        );
+        assert_eq!(
+            Color::rgb(0xff, 0x00, 0x00),
+            merged.representatives[&Color::rgb(0xff, 0x00, 0x00)]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        use crate::color::repr::{ColorRepr, WithColorRepr};
+
+        let colors = [
+            (Color::rgba(0x32, 0x32, 0x32, 0x80), 1),
+            (Color::rgba(0x33, 0x33, 0x33, 0x80), 3),
+            (Color::rgb(0xff, 0x00, 0x00), 1),
+        ];
+        let merged = merge(&colors, Representative::MostFrequent);
+        for repr in [ColorRepr::Hex, ColorRepr::Rgba, ColorRepr::Css] {
+            let json = serde_json::to_string(&WithColorRepr(&merged, repr)).unwrap();
+            let parsed: MergedColors = serde_json::from_str(&json).unwrap();
+            assert_eq!(merged.colors, parsed.colors, "{repr:?}");
+            assert_eq!(merged.representatives, parsed.representatives, "{repr:?}");
+        }
+        let json = serde_json::to_value(&merged).unwrap();
+        assert_eq!(
+            serde_json::json!({"original": "#ff0000", "representative": "#ff0000"}),
+            json["representatives"][2]
+        );
+    }
+
+    #[test]
+    fn test_original_representative() {
+        let colors = [
+            (Color::rgb(0x32, 0x32, 0x32), 1),
+            (Color::rgb(0x33, 0x33, 0x33), 3),
+        ];
+        let merged = merge(&colors, Representative::Original);
+        assert_eq!(vec![Color::rgb(0x32, 0x32, 0x32)], merged.colors);
+    }
+
+    #[test]
+    fn test_centroid_representative() {
+        let colors = [
+            (Color::rgb(0x32, 0x32, 0x32), 1),
+            (Color::rgb(0x34, 0x34, 0x34), 1),
+        ];
+        let merged = merge(&colors, Representative::Centroid);
+        assert_eq!(vec![Color::rgb(0x33, 0x33, 0x33)], merged.colors);
+    }
+
+    #[test]
+    fn test_threshold_keeps_distinct_colors() {
+        let colors = [
+            (Color::rgb(0x33, 0x33, 0x33), 1),
+            (Color::rgb(0x66, 0x66, 0x66), 1),
+        ];
+        let merged = merge_similar(
+            &colors,
+            &MergeOptions {
+                metric: DifferenceMetric::Rgb,
+                threshold: 10.,
+                representative: Representative::MostFrequent,
+            },
+        );
+        assert_eq!(2, merged.colors.len());
+    }
+}
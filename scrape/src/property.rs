@@ -0,0 +1,141 @@
+use crate::color::Color;
+use crate::{ColorDeclaration, SiteColors};
+
+/// Groups of CSS properties that colors are declared with.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PropertyCategory {
+    /// `color`
+    Text,
+    /// `background` and `background-*`
+    Background,
+    /// `border` and `border-*`
+    Border,
+    Fill,
+    Stroke,
+    BoxShadow,
+    TextShadow,
+    /// `outline` and `outline-color`
+    Outline,
+    CaretColor,
+    AccentColor,
+    /// `text-decoration` and `text-decoration-color`
+    TextDecoration,
+    /// Custom properties such as `--brand`.
+    Custom,
+    Other,
+}
+
+impl PropertyCategory {
+    pub fn of(property: &str) -> Self {
+        let property = property.to_ascii_lowercase();
+        match property.as_str() {
+            "color" => PropertyCategory::Text,
+            "fill" => PropertyCategory::Fill,
+            "stroke" => PropertyCategory::Stroke,
+            "box-shadow" => PropertyCategory::BoxShadow,
+            "text-shadow" => PropertyCategory::TextShadow,
+            "outline" | "outline-color" => PropertyCategory::Outline,
+            "caret-color" => PropertyCategory::CaretColor,
+            "accent-color" => PropertyCategory::AccentColor,
+            "text-decoration" | "text-decoration-color" => PropertyCategory::TextDecoration,
+            p if p.starts_with("--") => PropertyCategory::Custom,
+            p if p == "background" || p.starts_with("background-") => PropertyCategory::Background,
+            p if p == "border" || p.starts_with("border-") => PropertyCategory::Border,
+            _ => PropertyCategory::Other,
+        }
+    }
+}
+
+impl SiteColors {
+    pub fn declarations_in(
+        &self,
+        category: PropertyCategory,
+    ) -> impl Iterator<Item = &ColorDeclaration> {
+        self.declarations
+            .iter()
+            .filter(move |declaration| declaration.category == category)
+    }
+
+    /// Distinct colors declared with properties of `category`, in the order first seen.
+    pub fn colors_in(&self, category: PropertyCategory) -> Vec<Color> {
+        let mut colors: Vec<Color> = Vec::new();
+        for declaration in self.declarations_in(category) {
+            if !colors.contains(&declaration.color) {
+                colors.push(declaration.color);
+            }
+        }
+        colors
+    }
+
+    /// Categories a color was declared with, in category order.
+    pub fn categories_of(&self, color: &Color) -> Vec<PropertyCategory> {
+        let mut categories: Vec<PropertyCategory> = self
+            .declarations
+            .iter()
+            .filter(|declaration| declaration.color == *color)
+            .map(|declaration| declaration.category)
+            .collect();
+        categories.sort();
+        categories.dedup();
+        categories
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_property_categories() {
+        let cases = [
+            ("color", PropertyCategory::Text),
+            ("background", PropertyCategory::Background),
+            ("Background-Color", PropertyCategory::Background),
+            ("border-left-color", PropertyCategory::Border),
+            ("border-block-end", PropertyCategory::Border),
+            ("fill", PropertyCategory::Fill),
+            ("stroke", PropertyCategory::Stroke),
+            ("box-shadow", PropertyCategory::BoxShadow),
+            ("text-shadow", PropertyCategory::TextShadow),
+            ("outline", PropertyCategory::Outline),
+            ("caret-color", PropertyCategory::CaretColor),
+            ("accent-color", PropertyCategory::AccentColor),
+            ("text-decoration-color", PropertyCategory::TextDecoration),
+            ("--border-color", PropertyCategory::Custom),
+            ("column-rule-color", PropertyCategory::Other),
+        ];
+        for (property, category) in cases {
+            assert_eq!(category, PropertyCategory::of(property), "{property}");
+        }
+    }
+
+    #[test]
+    fn test_query_by_category() {
+        let css = "body { color: #111; background: #fff; } \
+                   a { color: blue; text-decoration-color: blue; } \
+                   .card { border: 1px solid #eee; box-shadow: 0 1px 2px #0003; } \
+                   svg { fill: #111; }";
+        let site = SiteColors {
+            declarations: crate::css::extract_color_declarations(css),
+            ..Default::default()
+        };
+        let black = Color::rgb(0x11, 0x11, 0x11);
+        let blue = Color::rgb(0, 0, 255);
+        assert_eq!(vec![black, blue], site.colors_in(PropertyCategory::Text));
+        assert_eq!(
+            vec![Color::rgb(255, 255, 255)],
+            site.colors_in(PropertyCategory::Background)
+        );
+        assert_eq!(
+            vec![Color::rgba(0, 0, 0, 0x33)],
+            site.colors_in(PropertyCategory::BoxShadow)
+        );
+        assert_eq!(1, site.declarations_in(PropertyCategory::Border).count());
+        assert_eq!(
+            vec![PropertyCategory::Text, PropertyCategory::Fill],
+            site.categories_of(&black)
+        );
+        assert!(site.colors_in(PropertyCategory::Stroke).is_empty());
+    }
+}
@@ -0,0 +1,411 @@
+use crate::color::{Color, Oklab};
+use crate::SiteColors;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuantizeMethod {
+    KMeans,
+    /// Like k-means but every palette color is one of the input colors.
+    KMedoids,
+    MedianCut,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct QuantizeOptions {
+    pub method: QuantizeMethod,
+    pub colors: usize,
+    /// Seeds k-means++ initialization so results are reproducible.
+    pub seed: u64,
+    pub max_iterations: usize,
+}
+
+impl Default for QuantizeOptions {
+    fn default() -> Self {
+        Self {
+            method: QuantizeMethod::KMeans,
+            colors: 8,
+            seed: 0x5eed,
+            max_iterations: 64,
+        }
+    }
+}
+
+/// A palette color with the share of the weight, between 0 and 1, that it summarizes.
+/// [`SiteColors::quantize`] weighs colors by the number of sources they were found in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeightedColor {
+    pub color: Color,
+    pub weight: f64,
+}
+
+impl SiteColors {
+    /// Quantizes colors weighted by [`color_counts`](Self::color_counts).
+    pub fn quantize(&self, options: &QuantizeOptions) -> Vec<WeightedColor> {
+        quantize(&self.color_counts(), options)
+    }
+}
+
+/// Reduces `(color, count)` pairs to at most `options.colors` colors, ordered by weight.
+pub fn quantize(color_counts: &[(Color, usize)], options: &QuantizeOptions) -> Vec<WeightedColor> {
+    let points: Vec<Point> = color_counts
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(color, count)| Point::new(color, *count as f64))
+        .collect();
+    if points.is_empty() || options.colors == 0 {
+        return Vec::new();
+    }
+    let clusters = if points.len() <= options.colors {
+        (0..points.len()).map(|i| vec![i]).collect()
+    } else {
+        match options.method {
+            QuantizeMethod::KMeans => k_means(&points, options),
+            QuantizeMethod::KMedoids => k_medoids(&points, options),
+            QuantizeMethod::MedianCut => median_cut(&points, options.colors),
+        }
+    };
+
+    let total: f64 = points.iter().map(|p| p.weight).sum();
+    let mut palette: Vec<WeightedColor> = clusters
+        .into_iter()
+        .filter(|members| !members.is_empty())
+        .map(|members| {
+            let color = match options.method {
+                _ if members.len() == 1 => points[members[0]].color,
+                QuantizeMethod::KMedoids => points[medoid(&points, &members)].color,
+                _ => mean(&points, &members).color(),
+            };
+            let weight = members.iter().map(|i| points[*i].weight).sum::<f64>() / total;
+            WeightedColor { color, weight }
+        })
+        .collect();
+    palette.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+    palette
+}
+
+#[derive(Clone, Copy)]
+struct Point {
+    color: Color,
+    lab: [f64; 3],
+    alpha: f64,
+    weight: f64,
+}
+
+impl Point {
+    fn new(color: &Color, weight: f64) -> Self {
+        let Oklab { l, a, b } = color.to_oklab();
+        Self {
+            color: *color,
+            lab: [l, a, b],
+            alpha: color.a as f64,
+            weight,
+        }
+    }
+
+    fn distance_sq(&self, lab: &[f64; 3]) -> f64 {
+        (0..3).map(|i| (self.lab[i] - lab[i]).powi(2)).sum()
+    }
+
+    fn color(&self) -> Color {
+        let [l, a, b] = self.lab;
+        let mut color = Oklab { l, a, b }.to_color();
+        color.a = self.alpha.round() as u8;
+        color
+    }
+}
+
+fn mean(points: &[Point], members: &[usize]) -> Point {
+    let mut sum = Point {
+        color: points[members[0]].color,
+        lab: [0.; 3],
+        alpha: 0.,
+        weight: 0.,
+    };
+    for point in members.iter().map(|i| &points[*i]) {
+        for c in 0..3 {
+            sum.lab[c] += point.lab[c] * point.weight;
+        }
+        sum.alpha += point.alpha * point.weight;
+        sum.weight += point.weight;
+    }
+    for c in 0..3 {
+        sum.lab[c] /= sum.weight;
+    }
+    sum.alpha /= sum.weight;
+    sum
+}
+
+fn medoid(points: &[Point], members: &[usize]) -> usize {
+    *members
+        .iter()
+        .min_by(|a, b| {
+            let cost = |candidate: usize| -> f64 {
+                members
+                    .iter()
+                    .map(|i| {
+                        points[*i].weight * points[*i].distance_sq(&points[candidate].lab).sqrt()
+                    })
+                    .sum()
+            };
+            cost(**a).total_cmp(&cost(**b))
+        })
+        .unwrap()
+}
+
+/// SplitMix64, enough randomness for seeding cluster centers reproducibly.
+struct Rng(u64);
+
+impl Rng {
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// k-means++ seeding weighted by count, returns indices of the chosen points.
+fn seed_centers(points: &[Point], k: usize, seed: u64) -> Vec<usize> {
+    let mut rng = Rng(seed);
+    let mut centers = Vec::with_capacity(k);
+    let total: f64 = points.iter().map(|p| p.weight).sum();
+    centers.push(pick(
+        points.iter().map(|p| p.weight),
+        total * rng.next_f64(),
+    ));
+    let mut nearest_sq: Vec<f64> = points
+        .iter()
+        .map(|p| p.distance_sq(&points[centers[0]].lab))
+        .collect();
+    while centers.len() < k {
+        let scores = points.iter().zip(&nearest_sq).map(|(p, d)| p.weight * d);
+        let total: f64 = scores.clone().sum();
+        if total <= 0. {
+            break;
+        }
+        let center = pick(scores, total * rng.next_f64());
+        centers.push(center);
+        for (i, p) in points.iter().enumerate() {
+            nearest_sq[i] = nearest_sq[i].min(p.distance_sq(&points[center].lab));
+        }
+    }
+    centers
+}
+
+fn pick(scores: impl Iterator<Item = f64>, target: f64) -> usize {
+    let mut cumulative = 0.;
+    let mut last = 0;
+    for (i, score) in scores.enumerate() {
+        if score <= 0. {
+            continue;
+        }
+        cumulative += score;
+        last = i;
+        if cumulative > target {
+            break;
+        }
+    }
+    last
+}
+
+fn assign(points: &[Point], centers: &[[f64; 3]]) -> Vec<usize> {
+    points
+        .iter()
+        .map(|p| {
+            (0..centers.len())
+                .min_by(|a, b| {
+                    p.distance_sq(&centers[*a])
+                        .total_cmp(&p.distance_sq(&centers[*b]))
+                })
+                .unwrap()
+        })
+        .collect()
+}
+
+fn group(assignments: &[usize], k: usize) -> Vec<Vec<usize>> {
+    let mut clusters = vec![Vec::new(); k];
+    for (i, cluster) in assignments.iter().enumerate() {
+        clusters[*cluster].push(i);
+    }
+    clusters
+}
+
+fn k_means(points: &[Point], options: &QuantizeOptions) -> Vec<Vec<usize>> {
+    let mut centers: Vec<[f64; 3]> = seed_centers(points, options.colors, options.seed)
+        .into_iter()
+        .map(|i| points[i].lab)
+        .collect();
+    let mut assignments = assign(points, &centers);
+    for _ in 0..options.max_iterations {
+        for (c, members) in group(&assignments, centers.len()).iter().enumerate() {
+            if !members.is_empty() {
+                centers[c] = mean(points, members).lab;
+            }
+        }
+        let next = assign(points, &centers);
+        if next == assignments {
+            break;
+        }
+        assignments = next;
+    }
+    group(&assignments, centers.len())
+}
+
+fn k_medoids(points: &[Point], options: &QuantizeOptions) -> Vec<Vec<usize>> {
+    let mut medoids = seed_centers(points, options.colors, options.seed);
+    let mut assignments = assign(points, &centers_of(points, &medoids));
+    for _ in 0..options.max_iterations {
+        for (c, members) in group(&assignments, medoids.len()).iter().enumerate() {
+            if !members.is_empty() {
+                medoids[c] = medoid(points, members);
+            }
+        }
+        let next = assign(points, &centers_of(points, &medoids));
+        if next == assignments {
+            break;
+        }
+        assignments = next;
+    }
+    group(&assignments, medoids.len())
+}
+
+fn centers_of(points: &[Point], indices: &[usize]) -> Vec<[f64; 3]> {
+    indices.iter().map(|i| points[*i].lab).collect()
+}
+
+fn median_cut(points: &[Point], k: usize) -> Vec<Vec<usize>> {
+    let mut boxes: Vec<Vec<usize>> = vec![(0..points.len()).collect()];
+    while boxes.len() < k {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, members)| members.len() > 1)
+            .map(|(i, members)| (i, widest_axis(points, members)))
+            .max_by(|a, b| a.1 .1.total_cmp(&b.1 .1));
+        let Some((box_index, (axis, _))) = widest else {
+            break;
+        };
+        let mut members = boxes.swap_remove(box_index);
+        members.sort_by(|a, b| points[*a].lab[axis].total_cmp(&points[*b].lab[axis]));
+        let half: f64 = members.iter().map(|i| points[*i].weight).sum::<f64>() / 2.;
+        let mut cumulative = 0.;
+        let mut split = 1;
+        for (n, i) in members.iter().enumerate() {
+            cumulative += points[*i].weight;
+            if cumulative >= half {
+                split = (n + 1).clamp(1, members.len() - 1);
+                break;
+            }
+        }
+        let upper = members.split_off(split);
+        boxes.push(members);
+        boxes.push(upper);
+    }
+    boxes
+}
+
+fn widest_axis(points: &[Point], members: &[usize]) -> (usize, f64) {
+    (0..3)
+        .map(|axis| {
+            let (min, max) = members.iter().fold((f64::MAX, f64::MIN), |(min, max), i| {
+                let v = points[*i].lab[axis];
+                (min.min(v), max.max(v))
+            });
+            (axis, max - min)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site_counts() -> Vec<(Color, usize)> {
+        vec![
+            (Color::rgb(250, 250, 250), 40),
+            (Color::rgb(255, 255, 255), 20),
+            (Color::rgb(20, 20, 20), 30),
+            (Color::rgb(30, 30, 30), 10),
+            (Color::rgb(220, 20, 60), 5),
+            (Color::rgb(200, 30, 50), 5),
+        ]
+    }
+
+    fn quantize_with(method: QuantizeMethod, colors: usize) -> Vec<WeightedColor> {
+        quantize(
+            &site_counts(),
+            &QuantizeOptions {
+                method,
+                colors,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_reduces_to_requested_colors() {
+        for method in [
+            QuantizeMethod::KMeans,
+            QuantizeMethod::KMedoids,
+            QuantizeMethod::MedianCut,
+        ] {
+            let palette = quantize_with(method, 3);
+            assert_eq!(3, palette.len());
+            let total: f64 = palette.iter().map(|c| c.weight).sum();
+            assert!((total - 1.).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_clusters_weighted_by_usage() {
+        for method in [QuantizeMethod::KMeans, QuantizeMethod::KMedoids] {
+            let palette = quantize_with(method, 3);
+            assert!((palette[0].weight - 60. / 110.).abs() < 1e-9, "{method:?}");
+            assert!((palette[1].weight - 40. / 110.).abs() < 1e-9, "{method:?}");
+            assert!((palette[2].weight - 10. / 110.).abs() < 1e-9, "{method:?}");
+            assert!(palette[0].color.r > 240, "{method:?}");
+            assert!(palette[1].color.r < 40, "{method:?}");
+        }
+    }
+
+    #[test]
+    fn test_median_cut_splits_at_weighted_median() {
+        let palette = quantize_with(QuantizeMethod::MedianCut, 2);
+        assert_eq!(2, palette.len());
+        assert!((palette[0].weight - 90. / 110.).abs() < 1e-9);
+        assert_eq!(Color::rgb(255, 255, 255), palette[1].color);
+    }
+
+    #[test]
+    fn test_k_medoids_picks_input_colors() {
+        let palette = quantize_with(QuantizeMethod::KMedoids, 3);
+        assert_eq!(Color::rgb(250, 250, 250), palette[0].color);
+        assert_eq!(Color::rgb(20, 20, 20), palette[1].color);
+    }
+
+    #[test]
+    fn test_k_means_is_deterministic() {
+        let counts: Vec<(Color, usize)> = (0..=255)
+            .step_by(5)
+            .map(|v| (Color::rgb(v as u8, (255 - v) as u8, 128), v % 7 + 1))
+            .collect();
+        let options = QuantizeOptions::default();
+        assert_eq!(quantize(&counts, &options), quantize(&counts, &options));
+        assert_eq!(8, quantize(&counts, &options).len());
+    }
+
+    #[test]
+    fn test_fewer_colors_than_requested() {
+        let palette = quantize(&[(Color::rgb(1, 2, 3), 2)], &QuantizeOptions::default());
+        assert_eq!(
+            vec![WeightedColor {
+                color: Color::rgb(1, 2, 3),
+                weight: 1.
+            }],
+            palette
+        );
+    }
+}
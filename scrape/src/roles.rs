@@ -0,0 +1,320 @@
+use crate::color::Color;
+use crate::{ColorDeclaration, PropertyCategory, SiteColors, ThemeColorSource};
+
+/// What a color is used for on a site.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Role {
+    PageBackground,
+    BodyText,
+    Link,
+    PrimaryAction,
+    Accent,
+    Border,
+    Success,
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoleCandidate {
+    pub role: Role,
+    pub color: Color,
+    /// From 0 to 1, the color's share of the evidence for the role, lowered when there
+    /// is little evidence for the role at all.
+    pub confidence: f64,
+}
+
+const PAGE_BACKGROUND_EVIDENCE: f64 = 3.;
+
+impl SiteColors {
+    /// Proposed roles for the site's colors from the properties and selectors they are
+    /// declared with, ordered by role and then by descending confidence.
+    pub fn roles(&self) -> Vec<RoleCandidate> {
+        let mut scores: Vec<(Role, Color, f64)> = Vec::new();
+        let mut add = |role: Role, color: Color, score: f64| match scores
+            .iter_mut()
+            .find(|(r, c, _)| *r == role && *c == color)
+        {
+            Some(entry) => entry.2 += score,
+            None => scores.push((role, color, score)),
+        };
+        for declaration in &self.declarations {
+            if declaration.color.a == 0 {
+                continue;
+            }
+            for (role, score) in evidence(declaration) {
+                add(role, declaration.color, score);
+            }
+        }
+        if let Some(background) = self.page_background {
+            add(Role::PageBackground, background, PAGE_BACKGROUND_EVIDENCE);
+        }
+        for theme in &self.theme_colors {
+            match theme.source {
+                ThemeColorSource::ManifestBackgroundColor => {
+                    add(Role::PageBackground, theme.color, 1.)
+                }
+                _ => add(Role::PrimaryAction, theme.color, 2.),
+            }
+        }
+
+        let mut candidates: Vec<RoleCandidate> = scores
+            .iter()
+            .map(|(role, color, score)| {
+                let total: f64 = scores
+                    .iter()
+                    .filter(|(r, _, _)| r == role)
+                    .map(|(_, _, s)| s)
+                    .sum();
+                RoleCandidate {
+                    role: *role,
+                    color: *color,
+                    confidence: score / (total + 2.),
+                }
+            })
+            .collect();
+        candidates.sort_by(|a, b| {
+            a.role
+                .cmp(&b.role)
+                .then(b.confidence.total_cmp(&a.confidence))
+        });
+        candidates
+    }
+
+    /// The most likely color for `role`.
+    pub fn role(&self, role: Role) -> Option<RoleCandidate> {
+        self.roles()
+            .into_iter()
+            .find(|candidate| candidate.role == role)
+    }
+}
+
+fn evidence(declaration: &ColorDeclaration) -> Vec<(Role, f64)> {
+    let category = declaration.category;
+    if category == PropertyCategory::Custom {
+        let words = words(&declaration.property);
+        return match status_role(&words) {
+            Some(role) => vec![(role, 3.)],
+            None => custom_property_evidence(&words),
+        };
+    }
+    let mut evidence = Vec::new();
+    for selector in declaration.selector.split(',') {
+        let words = words(selector);
+        if let Some(role) = status_role(&words) {
+            evidence.push((role, 3.));
+            continue;
+        }
+        let subject = subject(selector);
+        let element = element(subject);
+        let root = matches!(element, "html" | "body") || subject.starts_with(":root");
+        let link = element == "a" || subject.contains(":link") || subject.contains(":visited");
+        let interactive_state = [":hover", ":focus", ":active"]
+            .iter()
+            .any(|state| subject.contains(state));
+        let button = element == "button" || has_any(&words, &["btn", "button", "cta", "submit"]);
+        let primary = has_any(&words, &["primary", "cta"]);
+        let accent = has_any(&words, &["accent", "secondary", "highlight", "brand"]);
+        let heading = matches!(element, "h1" | "h2" | "h3" | "h4" | "h5" | "h6");
+        match category {
+            PropertyCategory::Background => {
+                if root {
+                    evidence.push((Role::PageBackground, 3.));
+                } else if button {
+                    evidence.push((Role::PrimaryAction, if primary { 3. } else { 1.5 }));
+                } else if accent {
+                    evidence.push((Role::Accent, 2.));
+                }
+            }
+            PropertyCategory::Text => {
+                if root {
+                    evidence.push((Role::BodyText, 3.));
+                } else if element == "p" {
+                    evidence.push((Role::BodyText, 1.));
+                } else if link && !button {
+                    evidence.push((Role::Link, if interactive_state { 1.5 } else { 3. }));
+                } else if primary && !button {
+                    evidence.push((Role::PrimaryAction, 1.));
+                } else if accent {
+                    evidence.push((Role::Accent, 2.));
+                } else if heading {
+                    evidence.push((Role::Accent, 1.));
+                }
+            }
+            PropertyCategory::Border | PropertyCategory::Outline => {
+                evidence.push((Role::Border, 1.))
+            }
+            _ => {}
+        }
+    }
+    evidence
+}
+
+fn custom_property_evidence(words: &[String]) -> Vec<(Role, f64)> {
+    let roles = [
+        (Role::PrimaryAction, &["primary", "cta"][..], 3.),
+        (Role::Link, &["link"][..], 2.),
+        (Role::Border, &["border", "outline", "divider"][..], 2.),
+        (Role::PageBackground, &["background", "bg"][..], 2.),
+        (
+            Role::BodyText,
+            &["text", "foreground", "fg", "body"][..],
+            2.,
+        ),
+        (
+            Role::Accent,
+            &["accent", "secondary", "highlight", "brand"][..],
+            2.,
+        ),
+    ];
+    roles
+        .iter()
+        .find(|(_, names, _)| has_any(words, names))
+        .map(|(role, _, score)| vec![(*role, *score)])
+        .unwrap_or_default()
+}
+
+fn status_role(words: &[String]) -> Option<Role> {
+    if has_any(words, &["success", "valid", "positive"]) {
+        Some(Role::Success)
+    } else if has_any(words, &["warning", "warn", "caution"]) {
+        Some(Role::Warning)
+    } else if has_any(
+        words,
+        &["error", "danger", "invalid", "destructive", "negative"],
+    ) {
+        Some(Role::Error)
+    } else {
+        None
+    }
+}
+
+/// Lowercased alphanumeric words of a selector or property name.
+fn words(s: &str) -> Vec<String> {
+    s.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_lowercase())
+        .collect()
+}
+
+fn has_any(words: &[String], names: &[&str]) -> bool {
+    words.iter().any(|word| names.contains(&word.as_str()))
+}
+
+/// Last compound selector, which selects the styled element.
+fn subject(selector: &str) -> &str {
+    selector
+        .trim()
+        .rsplit(|c: char| c.is_whitespace() || matches!(c, '>' | '+' | '~'))
+        .next()
+        .unwrap_or("")
+}
+
+fn element(compound: &str) -> &str {
+    let end = compound
+        .find(['.', '#', ':', '['])
+        .unwrap_or(compound.len());
+    &compound[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn declaration(selector: &str, property: &str, color: Color) -> ColorDeclaration {
+        ColorDeclaration {
+            selector: selector.to_string(),
+            property: property.to_string(),
+            category: PropertyCategory::of(property),
+            color,
+            origin: crate::StyleOrigin::default(),
+        }
+    }
+
+    #[test]
+    fn test_roles() {
+        let white = Color::rgb(255, 255, 255);
+        let text = Color::rgb(0x11, 0x11, 0x11);
+        let blue = Color::rgb(0x25, 0x63, 0xeb);
+        let dark_blue = Color::rgb(0x1d, 0x4e, 0xd8);
+        let red = Color::rgb(0xdc, 0x26, 0x26);
+        let gray = Color::rgb(0xe5, 0xe7, 0xeb);
+        let site = SiteColors {
+            declarations: vec![
+                declaration("html, body", "background-color", white),
+                declaration("body", "color", text),
+                declaration("main a", "color", blue),
+                declaration("a:hover", "color", dark_blue),
+                declaration(".btn-primary", "background", blue),
+                declaration(".btn-primary", "color", white),
+                declaration(".card", "border", gray),
+                declaration("hr", "border-top-color", gray),
+                declaration(".alert-danger", "color", red),
+                declaration(":root", "--color-error", red),
+                declaration(".overlay", "background", Color::rgba(0, 0, 0, 0)),
+            ],
+            page_background: Some(white),
+            ..Default::default()
+        };
+        let best = |role| site.role(role).map(|candidate| candidate.color);
+        assert_eq!(Some(white), best(Role::PageBackground));
+        assert_eq!(Some(text), best(Role::BodyText));
+        assert_eq!(Some(blue), best(Role::Link));
+        assert_eq!(Some(blue), best(Role::PrimaryAction));
+        assert_eq!(Some(gray), best(Role::Border));
+        assert_eq!(Some(red), best(Role::Error));
+        assert_eq!(None, best(Role::Success));
+
+        let roles = site.roles();
+        assert!(roles.windows(2).all(|w| w[0].role <= w[1].role));
+        let links: Vec<_> = roles.iter().filter(|c| c.role == Role::Link).collect();
+        assert_eq!(2, links.len());
+        assert!(links[0].confidence > links[1].confidence);
+        assert!(roles.iter().all(|c| c.confidence > 0. && c.confidence < 1.));
+        let page_background = site.role(Role::PageBackground).unwrap();
+        let body_text = site.role(Role::BodyText).unwrap();
+        assert!(page_background.confidence > body_text.confidence);
+    }
+
+    #[test]
+    fn test_custom_property_roles() {
+        let site = SiteColors {
+            declarations: vec![
+                declaration(":root", "--brand-primary", Color::rgb(0xe4, 0x00, 0x2b)),
+                declaration(":root", "--accent", Color::rgb(0xff, 0xc1, 0x07)),
+                declaration(":root", "--success-bg", Color::rgb(0x16, 0xa3, 0x4a)),
+            ],
+            ..Default::default()
+        };
+        let roles: Vec<Role> = site.roles().iter().map(|c| c.role).collect();
+        assert_eq!(
+            vec![Role::PrimaryAction, Role::Accent, Role::Success],
+            roles
+        );
+    }
+
+    #[test]
+    fn test_theme_colors_suggest_primary_action() {
+        let red = Color::rgb(0xe4, 0x00, 0x2b);
+        let site = SiteColors {
+            declarations: vec![declaration("a", "color", red)],
+            theme_colors: vec![crate::ThemeColor {
+                color: red,
+                source: ThemeColorSource::MetaThemeColor,
+                media: None,
+            }],
+            ..Default::default()
+        };
+        assert_eq!(Some(red), site.role(Role::PrimaryAction).map(|c| c.color));
+    }
+
+    #[test]
+    fn test_selector_subject() {
+        assert_eq!("a:hover", subject("nav > ul a:hover"));
+        assert_eq!("a", element("a:hover"));
+        assert_eq!("", element(".btn"));
+        assert_eq!(vec!["btn", "primary", "hover"], words(".btn-primary:hover"));
+    }
+}
@@ -0,0 +1,374 @@
+use crate::color::Color;
+use crate::css::extract_fragment_colors;
+use crate::PropertyCategory;
+
+/// Confidence of a color declared in a CSS string, such as a styled component's template.
+const CSS_CONFIDENCE: f64 = 0.6;
+/// Confidence of a color assigned to a style object property, such as `{ color: '#fff' }`.
+const STYLE_OBJECT_CONFIDENCE: f64 = 0.5;
+/// Confidence of a string that is nothing but a color, such as `'#e4002b'`.
+const LITERAL_CONFIDENCE: f64 = 0.3;
+
+/// A color found in a JavaScript string or template literal. Scripts hold colors for
+/// CSS-in-JS libraries such as styled-components and Emotion, but also for anything else,
+/// so these are less certain than stylesheet colors.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScriptColor {
+    pub color: Color,
+    /// CSS property the color was declared with, when there is one.
+    pub property: Option<String>,
+    /// URL of the script, `None` for inline scripts.
+    pub url: Option<String>,
+    /// From 0 to 1, how likely the color is used for styling.
+    pub confidence: f64,
+}
+
+/// Colors of the string and template literals of a script.
+pub(crate) fn extract_script_colors(js: &str, url: Option<&str>) -> Vec<ScriptColor> {
+    let mut colors = Vec::new();
+    for literal in string_literals(js) {
+        let mut push = |color, property: Option<String>, confidence| {
+            colors.push(ScriptColor {
+                color,
+                property,
+                url: url.map(str::to_string),
+                confidence,
+            })
+        };
+        let value = literal.value.trim();
+        if let Some(color) = parse_literal_color(value) {
+            match literal.key {
+                Some(key) => push(color, Some(kebab_case(&key)), STYLE_OBJECT_CONFIDENCE),
+                None => push(color, None, LITERAL_CONFIDENCE),
+            }
+        } else if looks_like_css(value) {
+            for (property, color) in extract_fragment_colors(value) {
+                if PropertyCategory::of(&property) != PropertyCategory::Other {
+                    push(color, Some(property), CSS_CONFIDENCE);
+                }
+            }
+        }
+    }
+    colors
+}
+
+/// String values of a script or JSON document that read like CSS, such as critical CSS
+/// serialized into a framework's payload.
+pub(crate) fn extract_css_strings(js: &str) -> Vec<String> {
+    string_literals(js)
+        .into_iter()
+        .map(|literal| literal.value)
+        .filter(|value| looks_like_css(value))
+        .collect()
+}
+
+struct StringLiteral {
+    /// Contents with template substitutions replaced by `var(--js)`.
+    value: String,
+    /// Object key the literal is assigned to, as in `{ backgroundColor: "#fff" }`.
+    key: Option<String>,
+}
+
+/// Only hex notation and color functions, since a bare keyword such as `"red"` is as likely
+/// to be text as a color.
+fn parse_literal_color(value: &str) -> Option<Color> {
+    let lowercase = value.to_ascii_lowercase();
+    let is_hex = lowercase.starts_with('#')
+        && matches!(lowercase.len(), 4 | 5 | 7 | 9)
+        && lowercase[1..].chars().all(|c| c.is_ascii_hexdigit());
+    let is_function = ["rgb(", "rgba(", "hsl(", "hsla(", "hwb(", "oklab(", "oklch("]
+        .iter()
+        .any(|function| lowercase.starts_with(function))
+        && lowercase.ends_with(')');
+    if is_hex || is_function {
+        value.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// At least one declaration of a property that takes colors, and nothing that reads like
+/// code.
+fn looks_like_css(value: &str) -> bool {
+    value.contains(':')
+        && (value.contains(';') || value.contains('{'))
+        && !value.contains("=>")
+        && !value.contains("function")
+        && value
+            .split(';')
+            .filter(|declaration| !declaration.trim().is_empty())
+            .any(|declaration| match declaration.split_once(':') {
+                Some((property, _)) => {
+                    let property = property.trim().rsplit('{').next().unwrap_or("").trim();
+                    property.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+                        && PropertyCategory::of(property) != PropertyCategory::Other
+                }
+                None => false,
+            })
+}
+
+fn kebab_case(key: &str) -> String {
+    let mut kebab = String::new();
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            kebab.push('-');
+            kebab.push(c.to_ascii_lowercase());
+        } else {
+            kebab.push(c);
+        }
+    }
+    kebab
+}
+
+/// String and template literals of a script, skipping comments and regular expressions.
+fn string_literals(js: &str) -> Vec<StringLiteral> {
+    let chars: Vec<char> = js.chars().collect();
+    let mut literals = Vec::new();
+    let mut i = 0;
+    // last significant char outside of literals and comments, to tell a regular
+    // expression from division
+    let mut previous = '\0';
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            '/' if previous == '\0' || "(,=:[!&|?{};+-*%<>~^".contains(previous) => {
+                i = skip_regex(&chars, i);
+                previous = '/';
+                continue;
+            }
+            '"' | '\'' | '`' => {
+                let key = object_key(&chars[..i]);
+                let (value, end) = read_literal(&chars, i);
+                literals.push(StringLiteral { value, key });
+                i = end;
+                previous = c;
+                continue;
+            }
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            previous = c;
+        }
+        i += 1;
+    }
+    literals
+}
+
+/// Reads the literal opening at `start`, returning its value and the index after it.
+fn read_literal(chars: &[char], start: usize) -> (String, usize) {
+    let quote = chars[start];
+    let mut value = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                match chars.get(i + 1) {
+                    Some('n' | 'r' | 't') => value.push(' '),
+                    Some(escaped) => value.push(*escaped),
+                    None => {}
+                }
+                i += 2;
+                continue;
+            }
+            c if c == quote => return (value, i + 1),
+            '\n' if quote != '`' => return (value, i),
+            '$' if quote == '`' && chars.get(i + 1) == Some(&'{') => {
+                i = skip_substitution(chars, i + 2);
+                value.push_str("var(--js)");
+                continue;
+            }
+            c => value.push(c),
+        }
+        i += 1;
+    }
+    (value, i)
+}
+
+/// Index after the `}` closing a template substitution whose body starts at `start`.
+fn skip_substitution(chars: &[char], start: usize) -> usize {
+    let mut depth = 1;
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            '"' | '\'' | '`' => {
+                i = read_literal(chars, i).1;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+fn skip_regex(chars: &[char], start: usize) -> usize {
+    let mut in_class = false;
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => return i + 1,
+            '\n' => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Key of an object property whose value starts after `before`.
+fn object_key(before: &[char]) -> Option<String> {
+    let mut i = before.len();
+    while i > 0 && before[i - 1].is_whitespace() {
+        i -= 1;
+    }
+    if i == 0 || before[i - 1] != ':' {
+        return None;
+    }
+    i -= 1;
+    while i > 0 && before[i - 1].is_whitespace() {
+        i -= 1;
+    }
+    let end = i;
+    while i > 0 && (before[i - 1].is_ascii_alphanumeric() || matches!(before[i - 1], '_' | '$')) {
+        i -= 1;
+    }
+    if i == end {
+        return None;
+    }
+    // a ternary's `cond ? a : "#fff"` is not an object
+    let key: String = before[i..end].iter().collect();
+    let mut j = i;
+    while j > 0 && before[j - 1].is_whitespace() {
+        j -= 1;
+    }
+    match j.checked_sub(1).map(|k| before[k]) {
+        None | Some('{' | ',') => Some(key),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(js: &str) -> Vec<(Color, Option<String>, f64)> {
+        extract_script_colors(js, None)
+            .into_iter()
+            .map(|color| (color.color, color.property, color.confidence))
+            .collect()
+    }
+
+    #[test]
+    fn test_styled_components() {
+        let js = r##"
+            const Button = styled.button`
+              color: #fff;
+              background: ${(props) => props.primary ? "#e4002b" : "transparent"};
+              border: 1px solid rgb(228 0 43);
+              &:hover { background-color: #b00020; }
+            `;
+        "##;
+        assert_eq!(
+            vec![
+                (
+                    Color::rgb(255, 255, 255),
+                    Some(String::from("color")),
+                    CSS_CONFIDENCE
+                ),
+                (
+                    Color::rgb(0xe4, 0x00, 0x2b),
+                    Some(String::from("border")),
+                    CSS_CONFIDENCE
+                ),
+                (
+                    Color::rgb(0xb0, 0x00, 0x20),
+                    Some(String::from("background-color")),
+                    CSS_CONFIDENCE
+                ),
+            ],
+            summary(js)
+        );
+    }
+
+    #[test]
+    fn test_style_objects_and_literals() {
+        let js = r##"
+            // const unused = "#000000";
+            /* "#111111" */
+            const styles = css({ backgroundColor: '#e4002b', "padding": 4, color: "hsl(0 0% 100%)" });
+            const brand = "#1a237e", label = "red", id = "#main";
+            const ok = x ? '#00ff00' : "#ff0000";
+            const re = /"#123456"/g, half = total / 2, other = count / "#654321".length;
+            document.head.insertAdjacentHTML("beforeend", "<style>.x{color:#222}</style>");
+        "##;
+        assert_eq!(
+            vec![
+                (
+                    Color::rgb(0xe4, 0x00, 0x2b),
+                    Some(String::from("background-color")),
+                    STYLE_OBJECT_CONFIDENCE
+                ),
+                (
+                    Color::rgb(255, 255, 255),
+                    Some(String::from("color")),
+                    STYLE_OBJECT_CONFIDENCE
+                ),
+                (Color::rgb(0x1a, 0x23, 0x7e), None, LITERAL_CONFIDENCE),
+                (Color::rgb(0, 255, 0), None, LITERAL_CONFIDENCE),
+                (Color::rgb(255, 0, 0), None, LITERAL_CONFIDENCE),
+                (Color::rgb(0x65, 0x43, 0x21), None, LITERAL_CONFIDENCE),
+                (
+                    Color::rgb(0x22, 0x22, 0x22),
+                    Some(String::from("color")),
+                    CSS_CONFIDENCE
+                ),
+            ],
+            summary(js)
+        );
+    }
+
+    #[test]
+    fn test_css_strings() {
+        let json =
+            r##"{"props":{"css":".a{color:#fff}\n.b{background:#000}","title":"Hi: there; you"}}"##;
+        assert_eq!(
+            vec![String::from(".a{color:#fff} .b{background:#000}")],
+            extract_css_strings(json)
+        );
+    }
+
+    #[test]
+    fn test_ignores_prose_and_code_strings() {
+        let js = r##"
+            const message = "Note: see #1234; thanks";
+            const template = `Total: ${total}; due ${date}`;
+            const handler = "function(e) { return e.color; }";
+        "##;
+        assert!(summary(js).is_empty());
+    }
+}
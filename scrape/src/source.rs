@@ -1,4 +1,137 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::graphic::GraphicKind;
+
+/// Where the CSS of a declaration came from.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StyleOrigin {
+    /// Linked or preloaded stylesheet.
+    #[default]
+    Stylesheet,
+    /// Plain `<style>` tag.
+    StyleTag,
+    /// `<style data-emotion>` tag rendered by Emotion.
+    Emotion,
+    /// `<style data-styled>` tag rendered by styled-components.
+    StyledComponents,
+    /// `<style id="__jsx-…">` tag rendered by styled-jsx.
+    StyledJsx,
+    /// `<style id="svelte-…">` tag rendered by Svelte.
+    Svelte,
+    /// CSS string in the `__NEXT_DATA__` payload of Next.js.
+    NextData,
+    /// `<style>` tag inside `<noscript>`.
+    Noscript,
+}
+
+#[derive(Debug, PartialEq)]
 pub(crate) enum SourceType {
-    LinkedCssFile { url: String },
-    StyleTagCss { css: String },
+    LinkedCssFile {
+        url: String,
+    },
+    StyleTagCss {
+        css: String,
+        origin: StyleOrigin,
+    },
+    /// Web app manifest linked with `<link rel="manifest">`.
+    Manifest {
+        url: String,
+    },
+    /// `<svg>` element markup.
+    InlineSvg {
+        svg: String,
+    },
+    /// SVG or raster image.
+    GraphicFile {
+        url: String,
+        kind: GraphicKind,
+    },
+    ScriptFile {
+        url: String,
+    },
+    InlineScript {
+        js: String,
+    },
+    /// Class names of every element in document order, repeated for each element.
+    ClassNames {
+        classes: Vec<String>,
+    },
+    /// `<meta>` tag declaring a color, with its lowercased name.
+    MetaColor {
+        name: String,
+        content: String,
+        media: Option<String>,
+    },
+}
+
+impl SourceType {
+    /// Identifies the source across the pages of a site: the URL of a fetched source, or
+    /// its kind and a hash of its content when inline.
+    pub(crate) fn key(&self, document_url: &str) -> String {
+        match self {
+            SourceType::LinkedCssFile { url }
+            | SourceType::Manifest { url }
+            | SourceType::GraphicFile { url, .. }
+            | SourceType::ScriptFile { url } => url.clone(),
+            SourceType::StyleTagCss { css, origin } => {
+                format!("style {origin:?} {}", hash(css))
+            }
+            SourceType::InlineSvg { svg } => format!("svg {}", hash(svg)),
+            SourceType::InlineScript { js } => format!("script {}", hash(js)),
+            // utility classes are counted per element, so every page adds to the counts
+            SourceType::ClassNames { .. } => format!("classes {document_url}"),
+            SourceType::MetaColor {
+                name,
+                content,
+                media,
+            } => format!("meta {}", hash((name, content, media))),
+        }
+    }
+}
+
+fn hash(content: impl Hash) -> String {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key() {
+        let url = String::from("https://host/main.css");
+        assert_eq!(
+            url,
+            SourceType::LinkedCssFile { url: url.clone() }.key("https://host/")
+        );
+        let style = |css: &str, origin| SourceType::StyleTagCss {
+            css: css.to_string(),
+            origin,
+        };
+        let key = style("a { color: red }", StyleOrigin::StyleTag).key("https://host/");
+        assert!(key.len() < 40, "{key}");
+        assert_eq!(
+            key,
+            style("a { color: red }", StyleOrigin::StyleTag).key("https://host/about")
+        );
+        assert_ne!(
+            key,
+            style("a { color: blue }", StyleOrigin::StyleTag).key("https://host/")
+        );
+        assert_ne!(
+            key,
+            style("a { color: red }", StyleOrigin::Emotion).key("https://host/")
+        );
+        let classes = SourceType::ClassNames {
+            classes: Vec::new(),
+        };
+        assert_ne!(
+            classes.key("https://host/"),
+            classes.key("https://host/about")
+        );
+    }
 }
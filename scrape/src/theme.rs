@@ -0,0 +1,170 @@
+use serde_json::Value;
+
+use crate::color::Color;
+use crate::SiteColors;
+
+/// Where a site declared a theme color.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThemeColorSource {
+    /// `<meta name="theme-color">`
+    MetaThemeColor,
+    /// `<meta name="msapplication-TileColor">`
+    TileColor,
+    /// `theme_color` of the web app manifest.
+    ManifestThemeColor,
+    /// `background_color` of the web app manifest, shown on the splash screen.
+    ManifestBackgroundColor,
+}
+
+/// A color a site explicitly declared for browser or OS UI.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThemeColor {
+    pub color: Color,
+    pub source: ThemeColorSource,
+    /// Media query the color applies to, such as `(prefers-color-scheme: dark)`.
+    pub media: Option<String>,
+}
+
+impl SiteColors {
+    /// Distinct theme colors other than the manifest background, most authoritative
+    /// source first.
+    pub fn brand_colors(&self) -> Vec<Color> {
+        let mut theme_colors: Vec<&ThemeColor> = self
+            .theme_colors
+            .iter()
+            .filter(|theme| theme.source != ThemeColorSource::ManifestBackgroundColor)
+            .collect();
+        theme_colors.sort_by_key(|theme| theme.source);
+        let mut colors: Vec<Color> = Vec::new();
+        for theme in theme_colors {
+            if !colors.contains(&theme.color) {
+                colors.push(theme.color);
+            }
+        }
+        colors
+    }
+}
+
+/// Theme color of a `<meta>` tag with a lowercased `name`.
+pub(crate) fn meta_theme_color(
+    name: &str,
+    content: &str,
+    media: Option<String>,
+) -> Option<ThemeColor> {
+    let source = match name {
+        "theme-color" => ThemeColorSource::MetaThemeColor,
+        "msapplication-tilecolor" => ThemeColorSource::TileColor,
+        _ => return None,
+    };
+    Some(ThemeColor {
+        color: content.parse().ok()?,
+        source,
+        media,
+    })
+}
+
+/// Colors of the top-level `theme_color` and `background_color` members of a web app
+/// manifest.
+pub(crate) fn manifest_theme_colors(manifest: &str) -> Vec<ThemeColor> {
+    let Ok(Value::Object(manifest)) = serde_json::from_str(manifest) else {
+        return Vec::new();
+    };
+    [
+        ("theme_color", ThemeColorSource::ManifestThemeColor),
+        (
+            "background_color",
+            ThemeColorSource::ManifestBackgroundColor,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(member, source)| {
+        Some(ThemeColor {
+            color: manifest.get(member)?.as_str()?.parse().ok()?,
+            source,
+            media: None,
+        })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meta_theme_color() {
+        assert_eq!(
+            Some(ThemeColor {
+                color: Color::rgb(0x42, 0x85, 0xf4),
+                source: ThemeColorSource::MetaThemeColor,
+                media: Some(String::from("(prefers-color-scheme: dark)")),
+            }),
+            meta_theme_color(
+                "theme-color",
+                " #4285F4 ",
+                Some(String::from("(prefers-color-scheme: dark)"))
+            )
+        );
+        assert_eq!(
+            Some(ThemeColorSource::TileColor),
+            meta_theme_color("msapplication-tilecolor", "navy", None).map(|t| t.source)
+        );
+        assert_eq!(None, meta_theme_color("theme-color", "var(--brand)", None));
+        assert_eq!(None, meta_theme_color("description", "red", None));
+    }
+
+    #[test]
+    fn test_manifest_theme_colors() {
+        let colors = manifest_theme_colors(
+            r##"{
+                "name": "Example",
+                "icons": [{ "src": "/icon.png", "sizes": "192x192" }],
+                "theme_color": "#e4002b",
+                "background_color" : "white"
+            }"##,
+        );
+        assert_eq!(2, colors.len());
+        assert_eq!(Color::rgb(0xe4, 0x00, 0x2b), colors[0].color);
+        assert_eq!(ThemeColorSource::ManifestThemeColor, colors[0].source);
+        assert_eq!(Color::rgb(255, 255, 255), colors[1].color);
+        assert_eq!(ThemeColorSource::ManifestBackgroundColor, colors[1].source);
+        assert!(manifest_theme_colors(r#"{"theme_color": "nope"}"#).is_empty());
+        assert!(manifest_theme_colors(r#"{"theme_color": "red""#).is_empty());
+        let nested = manifest_theme_colors(
+            r##"{
+                "name": "Say \"theme_color\": \"red\"",
+                "shortcuts": [{ "name": "Dark", "theme_color": "#000000" }],
+                "background_color": "#fafafa"
+            }"##,
+        );
+        assert_eq!(1, nested.len());
+        assert_eq!(Color::rgb(0xfa, 0xfa, 0xfa), nested[0].color);
+        assert_eq!(ThemeColorSource::ManifestBackgroundColor, nested[0].source);
+    }
+
+    #[test]
+    fn test_brand_colors() {
+        let theme = |color, source| ThemeColor {
+            color,
+            source,
+            media: None,
+        };
+        let red = Color::rgb(0xe4, 0x00, 0x2b);
+        let navy = Color::rgb(0, 0, 0x80);
+        let site = SiteColors {
+            theme_colors: vec![
+                theme(
+                    Color::rgb(255, 255, 255),
+                    ThemeColorSource::ManifestBackgroundColor,
+                ),
+                theme(navy, ThemeColorSource::TileColor),
+                theme(red, ThemeColorSource::ManifestThemeColor),
+                theme(red, ThemeColorSource::MetaThemeColor),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(vec![red, navy], site.brand_colors());
+    }
+}